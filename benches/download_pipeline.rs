@@ -0,0 +1,68 @@
+//! 对比分块 `join_all` 与滑动窗口流水线在慢速节点上的下载耗时
+//!
+//! 运行: `cargo bench --bench download_pipeline`
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use futures::future::join_all;
+use serde_json::json;
+
+use common::{download_options, test_config, Behavior, MockBook, MockServer};
+use fqdl::client::FanqieClient;
+
+const CHAPTERS: usize = 300;
+const WORKERS: usize = 16;
+
+#[tokio::main]
+async fn main() {
+    let server = MockServer::start();
+    let book = MockBook::sample("9001", CHAPTERS);
+    server.add_book(book.clone());
+
+    // 大部分请求 20ms 返回，每 10 个请求中有 1 个 400ms 的长尾
+    let slow_items: HashMap<String, Duration> = book.chapters
+        .iter()
+        .skip(6)
+        .step_by(10)
+        .map(|c| (c.chapter_id.clone(), Duration::from_millis(400)))
+        .collect();
+    server.set_behavior("/api/content", Behavior {
+        latency: Duration::from_millis(20),
+        slow_items,
+        ..Default::default()
+    });
+
+    let config = test_config(&[&server.base_url], json!({ "max_workers": WORKERS, "max_retries": 0 }));
+    let client = FanqieClient::from_config(config).unwrap();
+
+    let mut options = download_options("9001", "", "txt");
+    options.allow_partial = true;
+    let downloader = client.downloader(options);
+
+    let chapters = downloader.get_chapters().await.unwrap();
+    assert_eq!(chapters.len(), CHAPTERS);
+
+    let start = Instant::now();
+    for chunk in chapters.chunks(WORKERS) {
+        let futures: Vec<_> = chunk
+            .iter()
+            .map(|c| downloader.download_chapter(&c.chapter_id))
+            .collect();
+        join_all(futures).await;
+    }
+    let chunked = start.elapsed();
+
+    let start = Instant::now();
+    let downloaded = downloader.download_all_chapters(&chapters, None).await.unwrap();
+    let pipelined = start.elapsed();
+    assert!(downloaded.missing.is_empty());
+
+    println!("{} 章, 并发 {}", CHAPTERS, WORKERS);
+    println!("分块 join_all: {:>8.2?}", chunked);
+    println!("滑动窗口:     {:>8.2?}", pipelined);
+    println!("加速比:       {:>8.2}x", chunked.as_secs_f64() / pipelined.as_secs_f64());
+}
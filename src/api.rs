@@ -1,32 +1,103 @@
 use std::sync::Arc;
-use std::time::Duration;
-use tokio::sync::{Semaphore, Mutex};
-use reqwest::Client;
+use std::time::{Duration, Instant};
+use tokio::sync::{Semaphore, Mutex, RwLock};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE, RETRY_AFTER, USER_AGENT};
+use reqwest::{Client, StatusCode};
 use serde::de::DeserializeOwned;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
-use crate::config::{AppConfig, get_config};
+use crate::config::{
+    redact_proxy, ApiSource, AppConfig, ConfigParams, NodeAuth, UserAgentRotation, DIRECT_PROXY, get_config,
+};
+use crate::device::DeviceIdentity;
 use crate::error::{FanqieError, Result};
+use crate::export::CoverImage;
+use crate::health::{CircuitState, NodeHealth};
+use crate::utils::{parse_retry_after, retry_after_secs, Backoff, TokenBucket};
 
-pub struct ApiClient {
+const DEFAULT_USER_AGENTS: &[&str] = &[
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/119.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0",
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Safari/605.1.15",
+];
+
+/// 发往某个节点的请求所用的客户端、请求头和认证信息
+struct NodeTransport {
     client: Client,
+    headers: HeaderMap,
+    auth: Option<NodeAuth>,
+}
+
+/// 本次运行中 iOS 设备的注册状态
+enum DeviceState {
+    Unregistered,
+    Registered(DeviceIdentity),
+    /// 注册失败后本次运行不再重试，避免每个章节都请求一次注册接口
+    Failed(String),
+}
+
+enum AttemptError {
+    Retryable(FanqieError),
+    /// 传输失败、响应无法解析或未知错误码，计入节点健康度后改用下一个节点
+    Fatal(FanqieError),
+    /// 节点正常响应但拒绝了请求 (不存在、付费锁定、无权访问等)，不计入节点健康度，改用下一个节点
+    Rejected(FanqieError),
+    /// 节点要求暂停请求，`retry_after` 为响应给出的等待时间
+    Throttled {
+        error: FanqieError,
+        retry_after: Option<Duration>,
+    },
+}
+
+pub struct ApiClient {
+    config: Arc<RwLock<AppConfig>>,
+    /// 请求不在节点列表中的地址时使用 (全局代理和请求头)
+    transport: NodeTransport,
+    /// 每个节点独立的客户端、请求头和认证信息
+    node_transports: HashMap<String, NodeTransport>,
+    user_agents: Vec<String>,
+    user_agent_rotation: UserAgentRotation,
+    throttle_codes: Vec<i32>,
+    device_invalid_codes: Vec<i32>,
     semaphore: Arc<Semaphore>,
     current_node: Arc<Mutex<String>>,
+    rate_limiter: Arc<TokenBucket>,
+    node_limiters: HashMap<String, Arc<TokenBucket>>,
+    health: std::sync::Mutex<NodeHealth>,
+    device: Mutex<DeviceState>,
 }
 
 impl ApiClient {
-    pub fn new(config: &AppConfig) -> Result<Self> {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(config.params.request_timeout))
-            .pool_max_idle_per_host(config.params.connection_pool_size)
-            .pool_idle_timeout(Duration::from_secs(60))
-            .user_agent(Self::random_user_agent())
-            .gzip(true)
-            .brotli(true)
-            .build()
-            .map_err(|e| FanqieError::ApiRequest(format!("创建 HTTP 客户端失败: {}", e)))?;
+    pub fn new(config: AppConfig) -> Result<Self> {
+        let shared = Arc::new(RwLock::new(config.clone()));
+        Self::build(&config, shared)
+    }
 
-        let semaphore = Arc::new(Semaphore::new(config.params.max_workers));
+    /// 与其他组件共享同一份配置，节点切换等修改对双方可见
+    pub async fn with_shared_config(config: Arc<RwLock<AppConfig>>) -> Result<Self> {
+        let config_guard = config.read().await;
+        Self::build(&config_guard, config.clone())
+    }
+
+    fn build(config: &AppConfig, shared: Arc<RwLock<AppConfig>>) -> Result<Self> {
+        let user_agents = if config.params.user_agents.is_empty() {
+            DEFAULT_USER_AGENTS.iter().map(|ua| ua.to_string()).collect()
+        } else {
+            config.params.user_agents.clone()
+        };
+
+        let transport = Self::build_transport(&config.params, None, &user_agents)?;
+        let node_transports = config.api_sources
+            .iter()
+            .map(|source| {
+                Self::build_transport(&config.params, Some(source), &user_agents)
+                    .map(|transport| (source.base_url.clone(), transport))
+            })
+            .collect::<Result<HashMap<_, _>>>()?;
+
+        let semaphore = Arc::new(Semaphore::new(config.params.workers()));
         
         let current_node = if let Some(node) = config.get_current_node() {
             node.base_url.clone()
@@ -35,22 +106,136 @@ impl ApiClient {
         };
         let current_node = Arc::new(Mutex::new(current_node));
 
+        let rate_limiter = Self::build_rate_limiter(
+            config.params.api_rate_limit,
+            config.params.rate_limit_window,
+        );
+
+        let node_limiters = config.api_sources
+            .iter()
+            .filter_map(|source| {
+                let interval = source.request_rate_limit
+                    .unwrap_or(config.params.request_rate_limit);
+                Self::build_node_limiter(interval)
+                    .map(|limiter| (source.base_url.clone(), limiter))
+            })
+            .collect();
+
         Ok(Self {
-            client,
+            config: shared,
+            transport,
+            node_transports,
+            user_agents,
+            user_agent_rotation: config.params.user_agent_rotation,
+            throttle_codes: config.params.throttle_codes.clone(),
+            device_invalid_codes: config.params.device_invalid_codes.clone(),
             semaphore,
             current_node,
+            rate_limiter,
+            node_limiters,
+            health: std::sync::Mutex::new(NodeHealth::from_params(&config.params)),
+            device: Mutex::new(DeviceState::Unregistered),
         })
     }
 
-    fn random_user_agent() -> &'static str {
-        let user_agents = [
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/119.0.0.0 Safari/537.36",
-            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.0.0 Safari/537.36",
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:121.0) Gecko/20100101 Firefox/121.0",
-            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.2 Safari/605.1.15",
-        ];
-        user_agents[rand::random::<usize>() % user_agents.len()]
+    fn build_transport(
+        params: &ConfigParams,
+        source: Option<&ApiSource>,
+        user_agents: &[String],
+    ) -> Result<NodeTransport> {
+        Ok(NodeTransport {
+            client: Self::build_http_client(params, source, Self::pick_user_agent(user_agents))?,
+            headers: Self::build_headers(params, source)?,
+            auth: source.and_then(|s| s.auth.clone()),
+        })
+    }
+
+    /// 节点自己的代理设置优先 (`direct` 表示直连)，否则使用全局代理，`no_proxy` 中的主机直连
+    fn build_http_client(params: &ConfigParams, source: Option<&ApiSource>, user_agent: &str) -> Result<Client> {
+        let mut builder = Client::builder()
+            .timeout(Duration::from_secs(params.request_timeout))
+            .pool_max_idle_per_host(params.connection_pool_size)
+            .pool_idle_timeout(Duration::from_secs(60))
+            .user_agent(user_agent)
+            .gzip(true)
+            .brotli(true);
+
+        match source.and_then(|s| s.proxy.as_deref()) {
+            Some(DIRECT_PROXY) => builder = builder.no_proxy(),
+            Some(proxy) => builder = builder.proxy(Self::parse_proxy(proxy)?),
+            None => {
+                if let Some(proxy) = &params.proxy {
+                    let no_proxy = reqwest::NoProxy::from_string(&params.no_proxy.join(","));
+                    builder = builder.proxy(Self::parse_proxy(proxy)?.no_proxy(no_proxy));
+                }
+            }
+        }
+
+        builder
+            .build()
+            .map_err(|e| FanqieError::ApiRequest(format!("创建 HTTP 客户端失败: {}", e)))
+    }
+
+    /// 支持 http、https、socks5 和 socks5h 代理，认证信息写在 URL 中
+    fn parse_proxy(proxy: &str) -> Result<reqwest::Proxy> {
+        reqwest::Proxy::all(proxy).map_err(|e| {
+            FanqieError::ConfigLoad(format!("代理地址无效 {}: {}", redact_proxy(proxy), e))
+        })
+    }
+
+    /// 内置请求头，依次被全局 `headers` 和节点的 `headers` 覆盖
+    fn build_headers(params: &ConfigParams, source: Option<&ApiSource>) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+
+        let builtin = Self::get_headers()
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()));
+        let configured = params.headers
+            .iter()
+            .chain(source.into_iter().flat_map(|s| s.headers.iter()))
+            .map(|(name, value)| (name.clone(), value.clone()));
+
+        for (name, value) in builtin.chain(configured) {
+            let header_name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| FanqieError::ConfigLoad(format!("请求头名称无效 {}: {}", name, e)))?;
+            let header_value = HeaderValue::from_str(&value)
+                .map_err(|e| FanqieError::ConfigLoad(format!("请求头 {} 的值无效: {}", name, e)))?;
+            headers.insert(header_name, header_value);
+        }
+
+        Ok(headers)
+    }
+
+    fn transport(&self, base_url: &str) -> &NodeTransport {
+        self.node_transports.get(base_url).unwrap_or(&self.transport)
+    }
+
+    /// 全局限流: 每 `window` 秒最多 `limit` 个请求，`limit` 为 0 时不限流，但被限流后仍会降速
+    fn build_rate_limiter(limit: u32, window: f64) -> Arc<TokenBucket> {
+        if limit == 0 || window <= 0.0 {
+            return Arc::new(TokenBucket::unlimited());
+        }
+        let capacity = limit as f64;
+        Arc::new(TokenBucket::new(capacity / window, capacity))
+    }
+
+    /// 节点限流: 同一节点两次请求之间至少间隔 `interval` 秒，`interval` 不大于 0 时不限流
+    fn build_node_limiter(interval: f64) -> Option<Arc<TokenBucket>> {
+        if interval <= 0.0 {
+            return None;
+        }
+        Some(Arc::new(TokenBucket::new(1.0 / interval, 1.0)))
+    }
+
+    async fn acquire_rate_limit(&self, base_url: &str) {
+        self.rate_limiter.acquire().await;
+        if let Some(limiter) = self.node_limiters.get(base_url) {
+            limiter.acquire().await;
+        }
+    }
+
+    fn pick_user_agent(user_agents: &[String]) -> &str {
+        user_agents[rand::random::<usize>() % user_agents.len()].as_str()
     }
 
     fn get_headers() -> HashMap<&'static str, &'static str> {
@@ -63,6 +248,88 @@ impl ApiClient {
         headers
     }
 
+    pub fn config(&self) -> Arc<RwLock<AppConfig>> {
+        self.config.clone()
+    }
+
+    fn health(&self) -> std::sync::MutexGuard<'_, NodeHealth> {
+        self.health.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// 当前各节点健康状态的快照
+    pub fn node_health(&self) -> NodeHealth {
+        self.health().clone()
+    }
+
+    /// 恢复之前保存的节点健康状态
+    pub fn restore_node_health(&self, health: NodeHealth) {
+        self.health().restore(health);
+    }
+
+    /// 当前使用的 iOS 设备，尚未注册时为 `None`
+    pub async fn device(&self) -> Option<DeviceIdentity> {
+        match &*self.device.lock().await {
+            DeviceState::Registered(identity) => Some(identity.clone()),
+            _ => None,
+        }
+    }
+
+    /// 使用之前保存的设备，跳过注册
+    pub async fn restore_device(&self, identity: DeviceIdentity) {
+        *self.device.lock().await = DeviceState::Registered(identity);
+    }
+
+    /// 返回已有的设备，没有时通过 `ios_register` 注册一个
+    pub async fn ensure_device(&self) -> Result<DeviceIdentity> {
+        let mut state = self.device.lock().await;
+        match &*state {
+            DeviceState::Registered(identity) => return Ok(identity.clone()),
+            DeviceState::Failed(message) => return Err(FanqieError::ApiRequest(message.clone())),
+            DeviceState::Unregistered => {}
+        }
+
+        let result = self.register_identity().await;
+        *state = match &result {
+            Ok(identity) => DeviceState::Registered(identity.clone()),
+            Err(e) => DeviceState::Failed(e.to_string()),
+        };
+        result
+    }
+
+    /// `stale` 被 iOS 接口拒绝后重新注册。其他请求已经换过设备时直接返回新设备，
+    /// 同一个失效的设备只重新注册一次
+    pub async fn renew_device(&self, stale: &DeviceIdentity) -> Result<DeviceIdentity> {
+        let mut state = self.device.lock().await;
+        match &*state {
+            DeviceState::Registered(identity) if identity != stale => return Ok(identity.clone()),
+            DeviceState::Failed(message) => return Err(FanqieError::ApiRequest(message.clone())),
+            _ => {}
+        }
+
+        let result = self.register_identity().await;
+        *state = match &result {
+            Ok(identity) => DeviceState::Registered(identity.clone()),
+            Err(e) => DeviceState::Failed(e.to_string()),
+        };
+        result
+    }
+
+    /// 忽略已有的设备重新注册
+    pub async fn reregister_device(&self) -> Result<DeviceIdentity> {
+        let mut state = self.device.lock().await;
+        let identity = self.register_identity().await?;
+        *state = DeviceState::Registered(identity.clone());
+        Ok(identity)
+    }
+
+    async fn register_identity(&self) -> Result<DeviceIdentity> {
+        let response = self.register_ios().await?;
+        match response.data {
+            Some(data) if !data.device_id.is_empty() => Ok(DeviceIdentity::new(data.device_id, data.install_id)),
+            _ => Err(FanqieError::ApiRequest("设备注册失败: 未返回设备ID".to_string())),
+        }
+    }
+
     pub async fn get_current_node(&self) -> String {
         self.current_node.lock().await.clone()
     }
@@ -76,70 +343,108 @@ impl ApiClient {
         endpoint: &str,
         params: &HashMap<&str, &str>,
     ) -> Result<T> {
+        self.request_on(endpoint, params, |_| true).await
+    }
+
+    /// 仅在满足 `node_filter` 的节点间依次尝试请求
+    async fn request_on<T, F>(
+        &self,
+        endpoint: &str,
+        params: &HashMap<&str, &str>,
+        node_filter: F,
+    ) -> Result<T>
+    where
+        T: DeserializeOwned,
+        F: Fn(&ApiSource) -> bool,
+    {
         let _permit = self.semaphore.acquire().await
             .map_err(|e| FanqieError::ApiRequest(format!("获取信号量失败: {}", e)))?;
 
-        let config = get_config().await;
-        let config_guard = config.read().await;
+        let config_guard = self.config.read().await;
 
-        let nodes: Vec<String> = config_guard.api_sources
+        let nodes: Vec<(usize, String)> = config_guard.api_sources
             .iter()
-            .map(|s| s.base_url.clone())
+            .enumerate()
+            .filter(|(_, s)| node_filter(s))
+            .map(|(index, s)| (index, s.base_url.clone()))
             .collect();
+        let nodes = self.health().order(nodes);
+        let current_index = config_guard.current_node_index;
+        let max_retries = config_guard.params.max_retries;
+        let backoff = Backoff::from_params(&config_guard.params);
+        let throttle_cooldown = Duration::from_secs(config_guard.params.throttle_cooldown);
+        let max_retry_after = Duration::from_secs(config_guard.params.max_retry_after);
+        drop(config_guard);
 
+        // 被限流的节点先让给其他节点，暂停结束后再排到队尾重试
+        let mut queue: VecDeque<(usize, String, u32)> = nodes
+            .into_iter()
+            .map(|(index, base_url)| (index, base_url, 0))
+            .collect();
         let mut last_error = None;
 
-        for (index, base_url) in nodes.iter().enumerate() {
-            let url = format!("{}{}", base_url, endpoint);
+        while let Some((index, base_url, mut attempt)) = queue.pop_front() {
+            // 其他请求刚让该节点进入限流暂停时，优先使用仍可用的节点
+            let throttled = self.health().throttle_remaining(&base_url).is_some();
+            if throttled && queue.iter().any(|(_, url, _)| self.health().throttle_remaining(url).is_none()) {
+                queue.push_back((index, base_url, attempt));
+                continue;
+            }
 
-            let mut request = self.client.get(&url)
-                .query(params);
+            let url = format!("{}{}", base_url, endpoint);
 
-            for (key, value) in Self::get_headers() {
-                request = request.header(key, value);
-            }
+            loop {
+                let throttled = self.health().throttle_remaining(&base_url);
+                if let Some(remaining) = throttled {
+                    tokio::time::sleep(remaining.min(max_retry_after)).await;
+                }
+                self.acquire_rate_limit(&base_url).await;
+                let started = Instant::now();
+                let result = self.send_once::<T>(&url, &base_url, params).await;
+                match &result {
+                    Ok(_) => {
+                        self.health().record_success(&base_url, started.elapsed());
+                        self.rate_limiter.recover();
+                    }
+                    Err(AttemptError::Throttled { error, retry_after }) => {
+                        let pause = retry_after.unwrap_or(throttle_cooldown).min(max_retry_after);
+                        self.health().record_throttled(&base_url, &error.to_string(), pause);
+                        self.rate_limiter.slow_down().await;
+                    }
+                    Err(AttemptError::Retryable(e)) | Err(AttemptError::Fatal(e)) => {
+                        self.health().record_failure(&base_url, &e.to_string());
+                    }
+                    Err(AttemptError::Rejected(_)) => {}
+                }
 
-            match request.send().await {
-                Ok(response) => {
-                    let status = response.status();
-                    if status.is_success() {
-                        match response.json::<T>().await {
-                            Ok(data) => {
-                                if index != config_guard.current_node_index {
-                                    drop(config_guard);
-                                    let config = get_config().await;
-                                    let mut config_guard = config.write().await;
-                                    config_guard.set_node(index);
-                                    self.set_current_node(base_url.clone()).await;
-                                }
-                                return Ok(data);
-                            }
-                            Err(e) => {
-                                last_error = Some(FanqieError::JsonParse(format!("{}: {}", url, e)));
-                            }
+                match result {
+                    Ok(data) => {
+                        if index != current_index {
+                            let mut config_guard = self.config.write().await;
+                            config_guard.set_node(index);
+                            drop(config_guard);
+                            self.set_current_node(base_url.clone()).await;
                         }
-                    } else if status.as_u16() >= 500 {
-                        last_error = Some(FanqieError::ApiNodeUnavailable(base_url.clone()));
-                        continue;
-                    } else {
-                        let status_code = status.as_u16();
-                        match response.json::<T>().await {
-                            Ok(data) => return Ok(data),
-                            Err(e) => {
-                                last_error = Some(FanqieError::ApiRequest(
-                                    format!("HTTP {}: {}", status_code, e)
-                                ));
-                            }
+                        return Ok(data);
+                    }
+                    Err(AttemptError::Throttled { error, .. }) => {
+                        if attempt < max_retries {
+                            queue.push_back((index, base_url.clone(), attempt + 1));
                         }
+                        last_error = Some(error);
+                        break;
                     }
-                }
-                Err(e) => {
-                    if e.is_timeout() {
-                        last_error = Some(FanqieError::Timeout);
-                    } else if e.is_connect() {
-                        last_error = Some(FanqieError::Network(format!("{}: {}", base_url, e)));
-                    } else {
-                        last_error = Some(FanqieError::ApiRequest(format!("{}: {}", base_url, e)));
+                    Err(AttemptError::Retryable(_))
+                        if attempt < max_retries && self.health().state(&base_url) != CircuitState::Open =>
+                    {
+                        tokio::time::sleep(backoff.delay(attempt)).await;
+                        attempt += 1;
+                    }
+                    Err(AttemptError::Retryable(e))
+                    | Err(AttemptError::Fatal(e))
+                    | Err(AttemptError::Rejected(e)) => {
+                        last_error = Some(e);
+                        break;
                     }
                 }
             }
@@ -148,6 +453,145 @@ impl ApiClient {
         Err(last_error.unwrap_or(FanqieError::AllNodesUnavailable))
     }
 
+    /// 只向指定节点发送一次请求，不重试、不切换节点，也不计入节点健康度，用于测速
+    pub async fn probe<T: DeserializeOwned>(
+        &self,
+        base_url: &str,
+        endpoint: &str,
+        params: &HashMap<&str, &str>,
+    ) -> Result<(T, Duration)> {
+        let url = format!("{}{}", base_url, endpoint);
+
+        self.acquire_rate_limit(base_url).await;
+        let started = Instant::now();
+        match self.send_once::<T>(&url, base_url, params).await {
+            Ok(data) => Ok((data, started.elapsed())),
+            Err(AttemptError::Retryable(e))
+            | Err(AttemptError::Fatal(e))
+            | Err(AttemptError::Rejected(e))
+            | Err(AttemptError::Throttled { error: e, .. }) => Err(e),
+        }
+    }
+
+    /// 对单个节点发起一次请求，并区分可重试 (超时、连接失败、5xx)、限流 (429 或限流错误码)、节点故障与业务错误
+    async fn send_once<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        base_url: &str,
+        params: &HashMap<&str, &str>,
+    ) -> std::result::Result<T, AttemptError> {
+        let transport = self.transport(base_url);
+        let mut request = transport.client.get(url)
+            .query(params)
+            .headers(transport.headers.clone());
+
+        // 配置中显式指定的 User-Agent 不参与轮换
+        if self.user_agent_rotation == UserAgentRotation::PerRequest
+            && !transport.headers.contains_key(USER_AGENT)
+        {
+            request = request.header(USER_AGENT, Self::pick_user_agent(&self.user_agents));
+        }
+
+        match &transport.auth {
+            Some(NodeAuth::Bearer { token }) => request = request.bearer_auth(token),
+            Some(NodeAuth::Query { param, token }) => request = request.query(&[(param, token)]),
+            None => {}
+        }
+
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                // reqwest 的错误信息包含完整 URL，以查询参数认证时会带上令牌
+                let e = e.without_url();
+                return Err(if e.is_timeout() {
+                    AttemptError::Retryable(FanqieError::Timeout)
+                } else if e.is_connect() {
+                    AttemptError::Retryable(FanqieError::Network(format!("{}: {}", base_url, e)))
+                } else {
+                    AttemptError::Fatal(FanqieError::ApiRequest(format!("{}: {}", base_url, e)))
+                });
+            }
+        };
+
+        let status = response.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            let retry_after = response.headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
+            return Err(AttemptError::Throttled {
+                error: FanqieError::RateLimited(format!("{} 返回 HTTP 429", base_url)),
+                retry_after,
+            });
+        }
+        if status.is_server_error() {
+            return Err(AttemptError::Retryable(FanqieError::ApiNodeUnavailable(base_url.to_string())));
+        }
+
+        let body = response.bytes().await.map_err(|e| {
+            if e.is_timeout() {
+                AttemptError::Retryable(FanqieError::Timeout)
+            } else {
+                AttemptError::Fatal(FanqieError::ApiRequest(format!("{}: {}", url, e.without_url())))
+            }
+        })?;
+
+        // 错误响应体 (外层或 data 内层的错误码) 与 HTTP 错误状态统一按错误码分类
+        let envelope = serde_json::from_slice::<ApiResponse<InnerStatus>>(&body).ok();
+        let failure = envelope.as_ref().and_then(ApiResponse::error_code).or_else(|| {
+            (!status.is_success()).then(|| (status.as_u16() as i32, None))
+        });
+        if let Some((code, message)) = failure {
+            let retry_after = envelope.as_ref().and_then(|e| e.retry_after()).and_then(retry_after_secs);
+            return Err(self.classify_error(base_url, params, code, message.as_deref(), retry_after));
+        }
+
+        serde_json::from_slice::<T>(&body).map_err(|e| {
+            AttemptError::Fatal(FanqieError::JsonParse(format!("{}: {}", url, e)))
+        })
+    }
+
+    /// 把错误码转换为具体的错误: 限流和服务器错误可重试，未知错误码视为节点故障，
+    /// 不存在、付费锁定和无权访问不影响节点健康度，改用下一个节点
+    fn classify_error(
+        &self,
+        base_url: &str,
+        params: &HashMap<&str, &str>,
+        code: i32,
+        message: Option<&str>,
+        retry_after: Option<Duration>,
+    ) -> AttemptError {
+        let detail = match message.filter(|m| !m.is_empty()) {
+            Some(message) => format!("{} 返回错误码 {}: {}", base_url, code, message),
+            None => format!("{} 返回错误码 {}", base_url, code),
+        };
+
+        if self.device_invalid_codes.contains(&code) {
+            return AttemptError::Rejected(FanqieError::DeviceInvalid(detail));
+        }
+
+        match ApiErrorKind::classify(code, message, &self.throttle_codes) {
+            ApiErrorKind::Throttled => AttemptError::Throttled {
+                error: FanqieError::RateLimited(detail),
+                retry_after,
+            },
+            ApiErrorKind::Server => AttemptError::Retryable(FanqieError::ApiNodeUnavailable(detail)),
+            ApiErrorKind::NotFound => AttemptError::Rejected(
+                match params.get("book_id").or_else(|| params.get("fq_id")) {
+                    Some(book_id) => FanqieError::BookNotFound(book_id.to_string()),
+                    None => FanqieError::NotFound(
+                        params.get("item_id").map(|id| format!("章节 {}", id)).unwrap_or(detail),
+                    ),
+                },
+            ),
+            ApiErrorKind::Locked => AttemptError::Rejected(FanqieError::Locked(
+                params.get("item_id").map(|id| format!("章节 {} ({})", id, detail)).unwrap_or(detail),
+            )),
+            ApiErrorKind::Forbidden => AttemptError::Rejected(FanqieError::Forbidden(detail)),
+            ApiErrorKind::Other => AttemptError::Fatal(FanqieError::ApiRequest(detail)),
+        }
+    }
+
     pub async fn search_books(&self, keyword: &str, offset: i32) -> Result<SearchResponse> {
         let offset_str = offset.to_string();
         let mut params = HashMap::new();
@@ -155,8 +599,7 @@ impl ApiClient {
         params.insert("tab_type", "3");
         params.insert("offset", &offset_str);
 
-        let config = get_config().await;
-        let config_guard = config.read().await;
+        let config_guard = self.config.read().await;
         let endpoint = config_guard.endpoints.search.clone();
         drop(config_guard);
 
@@ -167,8 +610,7 @@ impl ApiClient {
         let mut params = HashMap::new();
         params.insert("book_id", book_id);
 
-        let config = get_config().await;
-        let config_guard = config.read().await;
+        let config_guard = self.config.read().await;
         let endpoint = config_guard.endpoints.detail.clone();
         drop(config_guard);
 
@@ -179,8 +621,7 @@ impl ApiClient {
         let mut params = HashMap::new();
         params.insert("book_id", book_id);
 
-        let config = get_config().await;
-        let config_guard = config.read().await;
+        let config_guard = self.config.read().await;
         let endpoint = config_guard.endpoints.book.clone();
         drop(config_guard);
 
@@ -191,8 +632,7 @@ impl ApiClient {
         let mut params = HashMap::new();
         params.insert("fq_id", book_id);
 
-        let config = get_config().await;
-        let config_guard = config.read().await;
+        let config_guard = self.config.read().await;
         let endpoint = config_guard.endpoints.directory.clone();
         drop(config_guard);
 
@@ -204,8 +644,7 @@ impl ApiClient {
         params.insert("tab", "小说");
         params.insert("item_id", chapter_id);
 
-        let config = get_config().await;
-        let config_guard = config.read().await;
+        let config_guard = self.config.read().await;
         let endpoint = config_guard.endpoints.content.clone();
         drop(config_guard);
 
@@ -216,28 +655,223 @@ impl ApiClient {
         let mut params = HashMap::new();
         params.insert("book_id", book_id);
 
-        let config = get_config().await;
-        let config_guard = config.read().await;
+        let config_guard = self.config.read().await;
         let endpoint = config_guard.endpoints.raw_full.clone();
         drop(config_guard);
 
+        self.request_on(&endpoint, &params, |s| s.supports_full_download).await
+    }
+
+    /// 通过 iOS 接口获取章节正文，`device_id` 来自 [`ApiClient::ensure_device`]
+    pub async fn get_ios_content(&self, chapter_id: &str, device_id: &str) -> Result<ChapterContentResponse> {
+        let mut params = HashMap::new();
+        params.insert("item_id", chapter_id);
+        params.insert("device_id", device_id);
+
+        let config_guard = self.config.read().await;
+        let endpoint = config_guard.endpoints.ios_content.clone();
+        drop(config_guard);
+
         self.request(&endpoint, &params).await
     }
+
+    pub async fn register_ios(&self) -> Result<DeviceRegisterResponse> {
+        let config_guard = self.config.read().await;
+        let endpoint = config_guard.endpoints.ios_register.clone();
+        drop(config_guard);
+
+        self.request(&endpoint, &HashMap::new()).await
+    }
+
+    pub async fn get_device_pool(&self) -> Result<DevicePoolResponse> {
+        let config_guard = self.config.read().await;
+        let endpoint = config_guard.endpoints.device_pool.clone();
+        drop(config_guard);
+
+        self.request(&endpoint, &HashMap::new()).await
+    }
+
+    pub async fn register_device(&self) -> Result<DeviceRegisterResponse> {
+        let config_guard = self.config.read().await;
+        let endpoint = config_guard.endpoints.device_register.clone();
+        drop(config_guard);
+
+        self.request(&endpoint, &HashMap::new()).await
+    }
+
+    pub async fn get_device_status(&self, device_id: &str) -> Result<DeviceStatusResponse> {
+        let mut params = HashMap::new();
+        params.insert("device_id", device_id);
+
+        let config_guard = self.config.read().await;
+        let endpoint = config_guard.endpoints.device_status.clone();
+        drop(config_guard);
+
+        self.request(&endpoint, &params).await
+    }
+
+    pub async fn get_manga_progress(&self, book_id: &str) -> Result<MangaProgressResponse> {
+        let mut params = HashMap::new();
+        params.insert("book_id", book_id);
+
+        let config_guard = self.config.read().await;
+        let endpoint = config_guard.endpoints.manga_progress.clone();
+        drop(config_guard);
+
+        self.request(&endpoint, &params).await
+    }
+
+    /// 下载封面图片。封面地址由 CDN 提供，不经过节点轮换，使用全局请求头发起一次请求
+    pub async fn fetch_cover(&self, url: &str) -> Result<CoverImage> {
+        let response = self.transport.client.get(url)
+            .headers(self.transport.headers.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| if e.is_timeout() {
+                FanqieError::Timeout
+            } else {
+                FanqieError::Network(format!("{}: {}", url, e.without_url()))
+            })?;
+
+        let content_type = response.headers()
+            .get(CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let data = response.bytes().await
+            .map_err(|e| FanqieError::Network(format!("{}: {}", url, e.without_url())))?;
+
+        CoverImage::from_bytes(data.to_vec(), content_type.as_deref())
+            .ok_or_else(|| FanqieError::ApiRequest(format!("封面不是图片: {}", url)))
+    }
 }
 
+/// 番茄 API 的统一响应格式: 外层 `code` 为 200 表示成功，
+/// 部分接口的 `data` 内还有一层 `code`/`message`，为 0 或 200 表示成功
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct ApiResponse<T> {
     pub code: i32,
+    #[serde(default, alias = "msg")]
     pub message: Option<String>,
     pub data: Option<T>,
 }
 
-#[derive(Debug, Clone, serde::Deserialize)]
-pub struct SearchResponse {
-    pub code: i32,
-    pub data: Option<SearchData>,
+impl ApiResponse<InnerStatus> {
+    /// 外层或内层的错误码及说明，成功时为 `None`
+    fn error_code(&self) -> Option<(i32, Option<String>)> {
+        if self.code != 200 {
+            return Some((self.code, self.message.clone()));
+        }
+        let inner = self.data.as_ref()?;
+        inner.code
+            .filter(|code| *code != 0 && *code != 200)
+            .map(|code| (code, inner.message.clone()))
+    }
+
+    fn retry_after(&self) -> Option<f64> {
+        self.data.as_ref().and_then(|d| d.retry_after)
+    }
+}
+
+/// 错误码的类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiErrorKind {
+    /// 书籍或章节不存在、已下架
+    NotFound,
+    /// 需要付费 (VIP) 或已锁定
+    Locked,
+    /// 未认证或无权访问 (节点令牌无效等)
+    Forbidden,
+    /// 请求过于频繁
+    Throttled,
+    /// 节点内部错误
+    Server,
+    Other,
 }
 
+impl ApiErrorKind {
+    pub fn classify(code: i32, message: Option<&str>, throttle_codes: &[i32]) -> Self {
+        let message = message.unwrap_or_default();
+        let mentions = |words: &[&str]| words.iter().any(|w| message.contains(w));
+
+        if code == 429 || throttle_codes.contains(&code) || mentions(&["频繁", "限流"]) {
+            ApiErrorKind::Throttled
+        } else if (500..600).contains(&code) {
+            ApiErrorKind::Server
+        } else if code == 402 || mentions(&["VIP", "付费", "购买", "锁定"]) {
+            ApiErrorKind::Locked
+        } else if code == 401 || code == 403 || mentions(&["无权", "未授权"]) {
+            ApiErrorKind::Forbidden
+        } else if code == 404 || code == 410 || mentions(&["不存在", "下架"]) {
+            ApiErrorKind::NotFound
+        } else {
+            ApiErrorKind::Other
+        }
+    }
+}
+
+/// 检查错误码时从 `data` 中读取的字段，`data` 不是对象时视为没有内层错误码
+#[derive(Debug, Default)]
+struct InnerStatus {
+    code: Option<i32>,
+    message: Option<String>,
+    retry_after: Option<f64>,
+}
+
+impl<'de> serde::Deserialize<'de> for InnerStatus {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct InnerVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for InnerVisitor {
+            type Value = InnerStatus;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("any JSON value")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> std::result::Result<InnerStatus, A::Error> {
+                let mut status = InnerStatus::default();
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "code" => status.code = map.next_value::<Option<i32>>().ok().flatten(),
+                        "message" | "msg" => status.message = map.next_value::<Option<String>>().ok().flatten(),
+                        "retry_after" => status.retry_after = map.next_value::<Option<f64>>().ok().flatten(),
+                        _ => {
+                            map.next_value::<serde::de::IgnoredAny>()?;
+                        }
+                    }
+                }
+                Ok(status)
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<InnerStatus, A::Error> {
+                while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {}
+                Ok(InnerStatus::default())
+            }
+
+            fn visit_bool<E>(self, _: bool) -> std::result::Result<InnerStatus, E> { Ok(InnerStatus::default()) }
+            fn visit_i64<E>(self, _: i64) -> std::result::Result<InnerStatus, E> { Ok(InnerStatus::default()) }
+            fn visit_u64<E>(self, _: u64) -> std::result::Result<InnerStatus, E> { Ok(InnerStatus::default()) }
+            fn visit_f64<E>(self, _: f64) -> std::result::Result<InnerStatus, E> { Ok(InnerStatus::default()) }
+            fn visit_str<E>(self, _: &str) -> std::result::Result<InnerStatus, E> { Ok(InnerStatus::default()) }
+            fn visit_unit<E>(self) -> std::result::Result<InnerStatus, E> { Ok(InnerStatus::default()) }
+        }
+
+        deserializer.deserialize_any(InnerVisitor)
+    }
+}
+
+pub type SearchResponse = ApiResponse<SearchData>;
+pub type BookDetailResponse = ApiResponse<BookDetailData>;
+pub type ChapterListResponse = ApiResponse<ChapterListDataWrapper>;
+pub type DirectoryResponse = ApiResponse<DirectoryData>;
+pub type ChapterContentResponse = ApiResponse<ChapterContent>;
+pub type RawFullResponse = ApiResponse<RawFullData>;
+pub type DeviceRegisterResponse = ApiResponse<DeviceRegisterData>;
+pub type DevicePoolResponse = ApiResponse<DevicePoolData>;
+pub type DeviceStatusResponse = ApiResponse<DeviceStatus>;
+pub type MangaProgressResponse = ApiResponse<MangaProgress>;
+
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct SearchData {
     pub search_tabs: Option<Vec<SearchTab>>,
@@ -254,7 +888,7 @@ pub struct SearchItem {
     pub book_data: Option<Vec<BookInfo>>,
 }
 
-#[derive(Debug, Clone, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct BookInfo {
     #[serde(default)]
     pub book_id: String,
@@ -288,24 +922,12 @@ impl BookInfo {
     }
 }
 
-#[derive(Debug, Clone, serde::Deserialize)]
-pub struct BookDetailResponse {
-    pub code: i32,
-    pub data: Option<BookDetailData>,
-}
-
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct BookDetailData {
     pub code: Option<i32>,
     pub data: Option<BookInfo>,
 }
 
-#[derive(Debug, Clone, serde::Deserialize)]
-pub struct ChapterListResponse {
-    pub code: i32,
-    pub data: Option<ChapterListDataWrapper>,
-}
-
 #[derive(Debug, Clone, serde::Deserialize)]
 pub struct ChapterListDataWrapper {
     pub code: Option<i32>,
@@ -337,6 +959,7 @@ impl ChapterInfoRaw {
             title: self.title.clone(),
             word_count: None,
             is_vip: None,
+            volume_name: self.volume_name.clone(),
         }
     }
 }
@@ -349,12 +972,9 @@ pub struct ChapterInfo {
     pub title: String,
     pub word_count: Option<i32>,
     pub is_vip: Option<bool>,
-}
-
-#[derive(Debug, Clone, serde::Deserialize)]
-pub struct DirectoryResponse {
-    pub code: i32,
-    pub data: Option<DirectoryData>,
+    /// 所属分卷名称，目录没有分卷信息时为 `None`
+    #[serde(default)]
+    pub volume_name: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
@@ -362,13 +982,7 @@ pub struct DirectoryData {
     pub lists: Option<Vec<ChapterInfo>>,
 }
 
-#[derive(Debug, Clone, serde::Deserialize)]
-pub struct ChapterContentResponse {
-    pub code: i32,
-    pub data: Option<ChapterContent>,
-}
-
-#[derive(Debug, Clone, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct ChapterContent {
     #[serde(default)]
     pub chapter_id: String,
@@ -376,35 +990,62 @@ pub struct ChapterContent {
     pub title: String,
     #[serde(default)]
     pub content: String,
+    /// 所属分卷名称，下载后按目录填写
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_name: Option<String>,
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
-pub struct RawFullResponse {
-    pub code: i32,
-    pub data: Option<RawFullData>,
+pub struct RawFullData {
+    pub chapters: Option<Vec<ChapterContent>>,
 }
 
 #[derive(Debug, Clone, serde::Deserialize)]
-pub struct RawFullData {
-    pub chapters: Option<Vec<ChapterContent>>,
+pub struct DeviceRegisterData {
+    #[serde(default)]
+    pub device_id: String,
+    pub install_id: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct DevicePoolData {
+    pub total: Option<i64>,
+    pub available: Option<i64>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct DeviceStatus {
+    pub device_id: Option<String>,
+    pub valid: Option<bool>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Deserialize)]
+pub struct MangaProgress {
+    pub book_id: Option<String>,
+    pub total: Option<i64>,
+    pub finished: Option<i64>,
+    pub status: Option<String>,
 }
 
-pub static API_CLIENT: once_cell::sync::OnceCell<Arc<ApiClient>> = once_cell::sync::OnceCell::new();
+static API_CLIENT: std::sync::RwLock<Option<Arc<ApiClient>>> = std::sync::RwLock::new(None);
 
+/// 基于全局配置创建全局 API 客户端，重复调用会替换之前的客户端
 pub async fn init_api_client() -> Result<Arc<ApiClient>> {
-    let config = get_config().await;
-    let config_guard = config.read().await;
-    let client = ApiClient::new(&config_guard)?;
-    let arc_client = Arc::new(client);
-    
-    API_CLIENT.set(arc_client.clone())
-        .map_err(|_| FanqieError::ApiRequest("API 客户端已初始化".to_string()))?;
+    let config = get_config()?;
+    let arc_client = Arc::new(ApiClient::with_shared_config(config).await?);
+    set_api_client(arc_client.clone());
 
     Ok(arc_client)
 }
 
-pub fn get_api_client() -> Arc<ApiClient> {
-    API_CLIENT.get()
-        .expect("API 客户端未初始化，请先调用 init_api_client()")
+pub fn set_api_client(client: Arc<ApiClient>) {
+    *API_CLIENT.write().unwrap_or_else(|e| e.into_inner()) = Some(client);
+}
+
+pub fn get_api_client() -> Result<Arc<ApiClient>> {
+    API_CLIENT.read()
+        .unwrap_or_else(|e| e.into_inner())
         .clone()
+        .ok_or_else(|| FanqieError::ApiRequest("API 客户端未初始化，请先调用 init_api_client()".to_string()))
 }
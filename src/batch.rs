@@ -3,7 +3,8 @@ use std::time::Instant;
 use tokio::sync::Semaphore;
 use futures::future::join_all;
 
-use crate::downloader::{DownloadOptions, download_book};
+use crate::api::{get_api_client, ApiClient};
+use crate::downloader::{DownloadOptions, Downloader};
 use crate::error::Result;
 
 #[derive(Debug, Clone)]
@@ -12,6 +13,13 @@ pub struct BatchOptions {
     pub save_path: String,
     pub format: String,
     pub max_concurrent: usize,
+    pub use_cache: bool,
+    pub allow_partial: bool,
+    pub full_download: bool,
+    pub filename_template: Option<String>,
+    pub epub_theme: Option<String>,
+    pub epub_css: Option<String>,
+    pub layout: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -24,12 +32,13 @@ pub struct BatchResult {
 }
 
 pub struct BatchDownloader {
+    client: Arc<ApiClient>,
     options: BatchOptions,
 }
 
 impl BatchDownloader {
-    pub fn new(options: BatchOptions) -> Self {
-        Self { options }
+    pub fn new(client: Arc<ApiClient>, options: BatchOptions) -> Self {
+        Self { client, options }
     }
 
     pub async fn run(&self) -> Result<Vec<BatchResult>> {
@@ -50,6 +59,14 @@ impl BatchDownloader {
                 let save_path = self.options.save_path.clone();
                 let format = self.options.format.clone();
                 let book_id = book_id.clone();
+                let client = self.client.clone();
+                let use_cache = self.options.use_cache;
+                let allow_partial = self.options.allow_partial;
+                let full_download = self.options.full_download;
+                let filename_template = self.options.filename_template.clone();
+                let epub_theme = self.options.epub_theme.clone();
+                let epub_css = self.options.epub_css.clone();
+                let layout = self.options.layout.clone();
 
                 async move {
                     let _permit = semaphore.acquire().await.unwrap();
@@ -63,9 +80,16 @@ impl BatchDownloader {
                         format,
                         start_chapter: None,
                         end_chapter: None,
+                        use_cache,
+                        allow_partial,
+                        full_download,
+                        filename_template,
+                        epub_theme,
+                        epub_css,
+                        layout,
                     };
 
-                    let result = match download_book(options).await {
+                    let result = match Downloader::new(client, options).download_book().await {
                         Ok(path) => {
                             let duration = start.elapsed().as_millis() as u64;
                             println!("[{}/{}] ✓ 下载完成: {} ({}ms)", 
@@ -116,6 +140,6 @@ impl BatchDownloader {
 }
 
 pub async fn batch_download(options: BatchOptions) -> Result<Vec<BatchResult>> {
-    let downloader = BatchDownloader::new(options);
+    let downloader = BatchDownloader::new(get_api_client()?, options);
     downloader.run().await
 }
@@ -0,0 +1,74 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::api::ChapterContent;
+use crate::error::{FanqieError, Result};
+
+/// 章节缓存，按 `<root>/<book_id>/<chapter_id>.json` 保存已下载的章节内容，
+/// 下载中断后再次运行时可以跳过已缓存的章节
+pub struct ChapterCache {
+    dir: PathBuf,
+}
+
+impl ChapterCache {
+    pub fn new<P: AsRef<Path>>(root: P, book_id: &str) -> Self {
+        Self {
+            dir: root.as_ref().join(sanitize_key(book_id)),
+        }
+    }
+
+    pub fn default_root() -> PathBuf {
+        dirs::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("fanqie-downloader/chapters")
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn chapter_path(&self, chapter_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", sanitize_key(chapter_id)))
+    }
+
+    pub fn contains(&self, chapter_id: &str) -> bool {
+        self.chapter_path(chapter_id).exists()
+    }
+
+    pub fn load(&self, chapter_id: &str) -> Option<ChapterContent> {
+        let content = fs::read_to_string(self.chapter_path(chapter_id)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    pub fn store(&self, chapter: &ChapterContent) -> Result<()> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| FanqieError::FileWrite(format!("创建缓存目录失败: {}", e)))?;
+
+        let json = serde_json::to_string(chapter)
+            .map_err(|e| FanqieError::JsonParse(format!("序列化章节失败: {}", e)))?;
+
+        // 先写临时文件再重命名，避免中断时留下不完整的缓存
+        let path = self.chapter_path(&chapter.chapter_id);
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, json)
+            .map_err(|e| FanqieError::FileWrite(format!("写入缓存失败: {}", e)))?;
+        fs::rename(&tmp_path, &path)
+            .map_err(|e| FanqieError::FileWrite(format!("写入缓存失败: {}", e)))?;
+
+        Ok(())
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir)
+                .map_err(|e| FanqieError::FileWrite(format!("清理缓存失败: {}", e)))?;
+        }
+        Ok(())
+    }
+}
+
+fn sanitize_key(key: &str) -> String {
+    key.chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
@@ -1,12 +1,22 @@
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
 
-use crate::config::init_config;
-use crate::api::init_api_client;
-use crate::search::{search, format_search_results, get_book_info, format_book_info};
-use crate::downloader::{DownloadOptions, download_book};
-use crate::batch::{BatchOptions, batch_download};
-use crate::export::ensure_output_dir;
+use crate::client::FanqieClient;
+use crate::config::{
+    init_config_dir, load_app_config_from, redact_proxy, resolve_config_path, save_api_source_order,
+    user_config_dir, NodeAuth, UserAgentRotation,
+};
+use crate::error::FanqieError;
+use crate::search::{format_search_results, format_book_info};
+use crate::downloader::DownloadOptions;
+use crate::batch::BatchOptions;
+use crate::update::UpdateOptions;
+use crate::export::{ensure_output_dir, EpubTheme, EXPORT_LAYOUTS};
+use crate::device::DeviceIdentity;
+use crate::health::NodeHealth;
+use crate::probe::{format_probe_results, ProbeOptions};
+use crate::settings::{SettingSource, Settings, UserConfig};
+use crate::validate::{validate_config_file, validate_user_config, ConfigIssue};
 
 #[derive(Parser)]
 #[command(name = "fqdl")]
@@ -14,6 +24,18 @@ use crate::export::ensure_output_dir;
 #[command(version)]
 #[command(about = "番茄小说下载器 - Rust 版本", long_about = None)]
 struct Cli {
+    #[arg(long, global = true, value_name = "PATH", help = "配置文件路径 (也可通过 FQDL_CONFIG 环境变量指定)")]
+    config: Option<String>,
+
+    #[arg(long, global = true, help = "每秒最多请求数 (覆盖配置中的 api_rate_limit)")]
+    rate: Option<u32>,
+
+    #[arg(long, global = true, help = "章节下载并发数 (覆盖配置中的 max_workers)")]
+    concurrency: Option<usize>,
+
+    #[arg(long, global = true, value_name = "URL", help = "请求使用的代理地址")]
+    proxy: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -40,17 +62,38 @@ enum Commands {
         #[arg(help = "书籍ID")]
         book_id: String,
         
-        #[arg(short, long, default_value = "~/Downloads")]
-        path: String,
+        #[arg(short, long, help = "保存目录 [默认: ~/Downloads]")]
+        path: Option<String>,
         
-        #[arg(short = 'f', long, default_value = "txt")]
-        format: String,
+        #[arg(short = 'f', long, help = "导出格式: txt/epub/html/md [默认: txt]")]
+        format: Option<String>,
+
+        #[arg(long, help = "文件名模板，可用 {title} {author} {book_id} [默认: {title}]")]
+        filename_template: Option<String>,
+
+        #[arg(long, value_parser = EpubTheme::NAMES, help = "EPUB 样式主题 [默认: default]")]
+        epub_theme: Option<String>,
+
+        #[arg(long, help = "追加到 EPUB 样式表的 CSS 文件")]
+        epub_css: Option<String>,
+
+        #[arg(long, value_parser = EXPORT_LAYOUTS, help = "导出布局: single 单个文件，dir 每章一个文件 (仅 txt/md) [默认: single]")]
+        layout: Option<String>,
         
         #[arg(short, long, help = "起始章节 (从1开始)")]
         start: Option<usize>,
         
         #[arg(short, long, help = "结束章节")]
         end: Option<usize>,
+
+        #[arg(long, help = "不使用章节缓存 (禁用断点续传)")]
+        no_cache: bool,
+
+        #[arg(long, help = "有章节下载失败时仍以成功状态退出")]
+        allow_partial: bool,
+
+        #[arg(long, help = "优先使用整本下载接口")]
+        full: bool,
     },
 
     #[command(about = "批量下载书籍")]
@@ -58,89 +101,275 @@ enum Commands {
         #[arg(help = "书籍ID列表 (空格分隔)")]
         book_ids: Vec<String>,
         
-        #[arg(short, long, default_value = "~/Downloads/FanqieNovels")]
-        path: String,
+        #[arg(short, long, help = "保存目录 [默认: ~/Downloads/FanqieNovels]")]
+        path: Option<String>,
         
-        #[arg(short = 'f', long, default_value = "txt")]
-        format: String,
+        #[arg(short = 'f', long, help = "导出格式: txt/epub/html/md [默认: txt]")]
+        format: Option<String>,
+
+        #[arg(long, help = "文件名模板，可用 {title} {author} {book_id} [默认: {title}]")]
+        filename_template: Option<String>,
+
+        #[arg(long, value_parser = EpubTheme::NAMES, help = "EPUB 样式主题 [默认: default]")]
+        epub_theme: Option<String>,
+
+        #[arg(long, help = "追加到 EPUB 样式表的 CSS 文件")]
+        epub_css: Option<String>,
+
+        #[arg(long, value_parser = EXPORT_LAYOUTS, help = "导出布局: single 单个文件，dir 每章一个文件 (仅 txt/md) [默认: single]")]
+        layout: Option<String>,
         
         #[arg(short = 'c', long, default_value = "3")]
         concurrent: usize,
         
         #[arg(short, long, help = "从文件读取书籍ID列表")]
         file: Option<String>,
+
+        #[arg(long, help = "不使用章节缓存 (禁用断点续传)")]
+        no_cache: bool,
+
+        #[arg(long, help = "有章节下载失败时仍以成功状态退出")]
+        allow_partial: bool,
+
+        #[arg(long, help = "优先使用整本下载接口")]
+        full: bool,
+    },
+
+    #[command(about = "更新已下载的连载书籍，只下载新章节")]
+    Update {
+        #[arg(help = "已导出的文件、清单文件路径或书籍ID")]
+        target: String,
+
+        #[arg(short, long, help = "按书籍ID查找时的保存目录 [默认: ~/Downloads]")]
+        path: Option<String>,
+
+        #[arg(long, help = "不使用章节缓存 (禁用断点续传)")]
+        no_cache: bool,
+
+        #[arg(long, help = "有章节下载失败时仍以成功状态退出")]
+        allow_partial: bool,
+    },
+
+    #[command(about = "显示各 API 节点的健康状态")]
+    Nodes {
+        #[command(subcommand)]
+        action: Option<NodesAction>,
+    },
+
+    #[command(about = "显示已注册的 iOS 设备和设备池状态")]
+    Device {
+        #[command(subcommand)]
+        action: Option<DeviceAction>,
     },
 
     #[command(about = "显示配置信息")]
     Config {
-        #[arg(short, long, help = "配置文件路径")]
+        #[arg(short, long, help = "配置文件路径 (同全局 --config)")]
         config_file: Option<String>,
+
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    #[command(about = "在用户配置目录写入默认的 fanqie.json 和带注释的 config.toml")]
+    Init {
+        #[arg(long, help = "覆盖已存在的文件")]
+        force: bool,
+    },
+
+    #[command(about = "检查配置文件中的问题")]
+    Validate {
+        #[arg(help = "要检查的 fanqie.json 路径 (默认为当前使用的配置文件)")]
+        path: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DeviceAction {
+    #[command(about = "注册 iOS 设备，供正文接口失败时使用备用接口")]
+    Register {
+        #[arg(long, help = "已有设备时也重新注册")]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum NodesAction {
+    #[command(about = "测试各节点的接口可用性和延迟，并按结果排序")]
+    Test {
+        #[arg(long, help = "测试使用的书籍ID")]
+        book_id: Option<String>,
+
+        #[arg(long, help = "测试搜索接口使用的关键词")]
+        keyword: Option<String>,
+
+        #[arg(long, help = "将排序后的节点顺序写回配置文件")]
+        save: bool,
     },
 }
 
 pub async fn run() -> crate::error::Result<()> {
     let cli = Cli::parse();
 
-    let config_path = find_config_file();
-    if let Some(path) = &config_path {
-        println!("使用配置文件: {}", path.display());
+    let config_arg = match &cli.command {
+        Commands::Config { config_file: Some(path), .. } => Some(path.clone()),
+        _ => cli.config.clone(),
+    };
+    let config_path = config_arg.map(|path| PathBuf::from(expand_tilde(&path)));
+
+    // init 和 validate 不依赖可用的配置，在加载配置之前处理
+    match cli.command {
+        Commands::Config { action: Some(ConfigAction::Init { force }), .. } => {
+            return cmd_config_init(force);
+        }
+        Commands::Config { action: Some(ConfigAction::Validate { path }), .. } => {
+            let path = path.map(|path| PathBuf::from(expand_tilde(&path))).or(config_path);
+            return cmd_config_validate(path);
+        }
+        _ => {}
     }
 
-    init_config().await?;
-    init_api_client().await?;
+    let mut config = load_app_config_from(config_path.as_deref())?;
+    println!("使用配置文件: {}", config.config_path.display());
 
-    match cli.command {
+    if let Some(rate) = cli.rate {
+        config.params.api_rate_limit = rate;
+        config.params.rate_limit_window = 1.0;
+    }
+
+    let mut settings = Settings::load(&config)?;
+    if let Some(concurrency) = cli.concurrency {
+        settings.concurrency.set(concurrency, SettingSource::Cli);
+    }
+    if let Some(proxy) = cli.proxy {
+        settings.proxy.set(Some(proxy), SettingSource::Cli);
+    }
+    settings.apply_to(&mut config);
+
+    let client = FanqieClient::from_config(config)?;
+
+    // 节点健康状态跨进程保留，避免每次启动都先尝试已知失效的节点
+    let health_path = NodeHealth::default_path();
+    if let Ok(health) = NodeHealth::load(&health_path) {
+        client.api().restore_node_health(health);
+    }
+
+    // 注册过的设备ID同样保留，iOS 备用正文接口不必每次重新注册
+    let device_path = DeviceIdentity::default_path();
+    let saved_device = DeviceIdentity::load(&device_path).ok();
+    if let Some(device) = saved_device.clone() {
+        client.api().restore_device(device).await;
+    }
+
+    let result = run_command(&client, &settings, cli.command).await;
+
+    if let Err(e) = client.api().node_health().save(&health_path) {
+        eprintln!("保存节点状态失败: {}", e);
+    }
+    if let Some(device) = client.api().device().await.filter(|d| saved_device.as_ref() != Some(d)) {
+        if let Err(e) = device.save(&device_path) {
+            eprintln!("保存设备信息失败: {}", e);
+        }
+    }
+
+    result
+}
+
+async fn run_command(client: &FanqieClient, settings: &Settings, command: Commands) -> crate::error::Result<()> {
+    match command {
         Commands::Search { keyword, offset } => {
-            cmd_search(keyword, offset).await?;
+            cmd_search(client, keyword, offset).await?;
         }
         Commands::Info { book_id } => {
-            cmd_info(book_id).await?;
+            cmd_info(client, book_id).await?;
         }
-        Commands::Download { book_id, path, format, start, end } => {
-            cmd_download(book_id, path, format, start, end).await?;
+        Commands::Download { book_id, path, format, filename_template, epub_theme, epub_css, layout, start, end, no_cache, allow_partial, full } => {
+            let options = DownloadOptions {
+                book_id,
+                save_path: path.unwrap_or_else(|| settings.save_path.value.clone()),
+                format: format.unwrap_or_else(|| settings.format.value.clone()),
+                start_chapter: start,
+                end_chapter: end,
+                use_cache: !no_cache,
+                allow_partial,
+                full_download: full,
+                filename_template: Some(filename_template.unwrap_or_else(|| settings.filename_template.value.clone())),
+                epub_theme,
+                epub_css,
+                layout,
+            };
+            cmd_download(client, options).await?;
         }
-        Commands::Batch { book_ids, path, format, concurrent, file } => {
-            cmd_batch(book_ids, path, format, concurrent, file).await?;
+        Commands::Batch { book_ids, path, format, filename_template, epub_theme, epub_css, layout, concurrent, file, no_cache, allow_partial, full } => {
+            let options = BatchOptions {
+                book_ids,
+                save_path: path.unwrap_or_else(|| settings.batch_save_path()),
+                format: format.unwrap_or_else(|| settings.format.value.clone()),
+                max_concurrent: concurrent.min(5),
+                use_cache: !no_cache,
+                allow_partial,
+                full_download: full,
+                filename_template: Some(filename_template.unwrap_or_else(|| settings.filename_template.value.clone())),
+                epub_theme,
+                epub_css,
+                layout,
+            };
+            cmd_batch(client, options, file).await?;
         }
-        Commands::Config { config_file } => {
-            cmd_config(config_file).await?;
+        Commands::Update { target, path, no_cache, allow_partial } => {
+            let options = UpdateOptions {
+                target,
+                save_path: path.unwrap_or_else(|| settings.save_path.value.clone()),
+                use_cache: !no_cache,
+                allow_partial,
+            };
+            cmd_update(client, options).await?;
         }
-    }
-
-    Ok(())
-}
-
-fn find_config_file() -> Option<PathBuf> {
-    let possible_paths = vec![
-        PathBuf::from("config/fanqie.json"),
-        PathBuf::from("fanqie.json"),
-    ];
-
-    for path in possible_paths {
-        if path.exists() {
-            return Some(path);
+        Commands::Nodes { action: None } => {
+            cmd_nodes(client).await?;
+        }
+        Commands::Nodes { action: Some(NodesAction::Test { book_id, keyword, save }) } => {
+            let mut options = ProbeOptions::default();
+            if let Some(book_id) = book_id {
+                options.book_id = book_id;
+            }
+            if let Some(keyword) = keyword {
+                options.keyword = keyword;
+            }
+            cmd_nodes_test(client, options, save).await?;
+        }
+        Commands::Device { action: None } => {
+            cmd_device(client).await?;
+        }
+        Commands::Device { action: Some(DeviceAction::Register { force }) } => {
+            cmd_device_register(client, force).await?;
+        }
+        Commands::Config { .. } => {
+            cmd_config(client, settings).await?;
         }
     }
 
-    None
+    Ok(())
 }
 
-async fn cmd_search(keyword: String, offset: i32) -> crate::error::Result<()> {
+async fn cmd_search(client: &FanqieClient, keyword: String, offset: i32) -> crate::error::Result<()> {
     println!("正在搜索: {}", keyword);
     
-    let result = search(&keyword, Some(offset)).await?;
+    let result = client.search(&keyword, Some(offset)).await?;
     println!("{}", format_search_results(&result.books));
     
     Ok(())
 }
 
-async fn cmd_info(book_id: String) -> crate::error::Result<()> {
+async fn cmd_info(client: &FanqieClient, book_id: String) -> crate::error::Result<()> {
     println!("正在获取书籍信息: {}", book_id);
     
-    let book_info = get_book_info(&book_id).await?;
+    let book_info = client.book_info(&book_id).await?;
     
-    let client = crate::api::get_api_client();
-    let chapter_response = client.get_chapter_list(&book_id).await?;
+    let chapter_response = client.api().get_chapter_list(&book_id).await?;
     
     let chapter_count = if let Some(wrapper) = chapter_response.data {
         if let Some(data) = wrapper.data {
@@ -149,10 +378,8 @@ async fn cmd_info(book_id: String) -> crate::error::Result<()> {
             } else if let Some(volumes) = data.chapter_list_with_volume {
                 let count: usize = volumes.iter().map(|v| v.len()).sum();
                 Some(count)
-            } else if let Some(chapters) = data.data {
-                Some(chapters.len())
             } else {
-                None
+                data.data.map(|chapters| chapters.len())
             }
         } else {
             None
@@ -166,92 +393,290 @@ async fn cmd_info(book_id: String) -> crate::error::Result<()> {
     Ok(())
 }
 
-async fn cmd_download(
-    book_id: String,
-    path: String,
-    format: String,
-    start: Option<usize>,
-    end: Option<usize>,
-) -> crate::error::Result<()> {
-    let save_path = expand_tilde(&path);
-    ensure_output_dir(&save_path)?;
-
-    let options = DownloadOptions {
-        book_id,
-        save_path,
-        format,
-        start_chapter: start,
-        end_chapter: end,
-    };
+async fn cmd_download(client: &FanqieClient, mut options: DownloadOptions) -> crate::error::Result<()> {
+    options.save_path = expand_tilde(&options.save_path);
+    options.epub_css = options.epub_css.as_deref().map(absolute_path);
+    ensure_output_dir(&options.save_path)?;
 
-    download_book(options).await?;
+    client.downloader(options).download_book().await?;
 
     println!("\n下载完成!");
     
     Ok(())
 }
 
-async fn cmd_batch(
-    book_ids: Vec<String>,
-    path: String,
-    format: String,
-    concurrent: usize,
-    file: Option<String>,
-) -> crate::error::Result<()> {
-    let mut all_book_ids = book_ids;
-
+async fn cmd_batch(client: &FanqieClient, mut options: BatchOptions, file: Option<String>) -> crate::error::Result<()> {
     if let Some(file_path) = file {
         let content = std::fs::read_to_string(&file_path)?;
         for line in content.lines() {
             let line = line.trim();
             if !line.is_empty() && !line.starts_with('#') {
-                all_book_ids.push(line.to_string());
+                options.book_ids.push(line.to_string());
             }
         }
     }
 
-    if all_book_ids.is_empty() {
+    if options.book_ids.is_empty() {
         println!("错误: 请提供至少一个书籍ID");
         return Ok(());
     }
 
-    let save_path = expand_tilde(&path);
-    ensure_output_dir(&save_path)?;
+    options.save_path = expand_tilde(&options.save_path);
+    options.epub_css = options.epub_css.as_deref().map(absolute_path);
+    ensure_output_dir(&options.save_path)?;
 
-    let options = BatchOptions {
-        book_ids: all_book_ids,
-        save_path,
-        format,
-        max_concurrent: concurrent.min(5),
-    };
+    let results = client.batch(options).run().await?;
+
+    let failed_count = results.iter().filter(|r| !r.success).count();
+    if failed_count > 0 {
+        return Err(crate::error::FanqieError::Download(
+            format!("{} 本书籍未能完整下载", failed_count)
+        ));
+    }
+
+    Ok(())
+}
+
+async fn cmd_update(client: &FanqieClient, mut options: UpdateOptions) -> crate::error::Result<()> {
+    options.target = expand_tilde(&options.target);
+    options.save_path = expand_tilde(&options.save_path);
+
+    let results = client.updater(options).run().await?;
+    let total_new: usize = results.iter().map(|r| r.new_chapters).sum();
+
+    println!("\n更新完成! 共新增 {} 章", total_new);
+
+    Ok(())
+}
+
+async fn cmd_nodes(client: &FanqieClient) -> crate::error::Result<()> {
+    let health = client.api().node_health();
+    let config = client.config();
+    let config_guard = config.read().await;
+    let now = chrono::Utc::now();
+
+    println!("\n节点状态:");
+    println!("{}", "=".repeat(100));
+    println!("{:<40} {:<6} {:>6} {:>6} {:>8} {:>10}  最近错误",
+        "节点", "状态", "成功", "失败", "连续失败", "平均延迟");
+    println!("{}", "-".repeat(100));
+
+    for source in &config_guard.api_sources {
+        let stats = health.get(&source.base_url).cloned().unwrap_or_default();
+        let latency = stats.avg_latency_ms
+            .map(|ms| format!("{:.0}ms", ms))
+            .unwrap_or_else(|| "-".to_string());
+
+        println!("{:<40} {:<6} {:>6} {:>6} {:>8} {:>10}  {}",
+            source.base_url,
+            stats.state(now).label(),
+            stats.successes,
+            stats.failures,
+            stats.consecutive_failures,
+            latency,
+            stats.last_error.as_deref().unwrap_or("-")
+        );
+
+        if let Some(until) = stats.open_until.filter(|until| *until > now) {
+            println!("{:<40} 熔断至 {}", "", until.with_timezone(&chrono::Local).format("%H:%M:%S"));
+        }
+        if let Some(until) = stats.throttled_until.filter(|until| *until > now) {
+            println!("{:<40} 限流暂停至 {}", "", until.with_timezone(&chrono::Local).format("%H:%M:%S"));
+        }
+    }
+
+    println!("{}", "=".repeat(100));
+    println!("连续失败 {} 次后熔断，冷却 {} 秒",
+        config_guard.params.circuit_failure_threshold, config_guard.params.circuit_cooldown);
+
+    Ok(())
+}
+
+async fn cmd_nodes_test(client: &FanqieClient, options: ProbeOptions, save: bool) -> crate::error::Result<()> {
+    println!("正在测试 {} 个节点 (书籍ID: {})...", client.config().read().await.api_sources.len(), options.book_id);
+
+    let probes = client.prober(options).run().await;
+    println!("{}", format_probe_results(&probes));
+
+    if save {
+        let config_path = client.config().read().await.config_path.clone();
+        let order: Vec<String> = probes.iter().map(|p| p.base_url.clone()).collect();
+        save_api_source_order(&config_path, &order)?;
+        println!("已将节点顺序保存到: {}", config_path.display());
+    }
+
+    Ok(())
+}
+
+async fn cmd_device(client: &FanqieClient) -> crate::error::Result<()> {
+    let api = client.api();
+
+    match api.device().await {
+        Some(device) => {
+            println!("设备ID: {}", device.device_id);
+            if let Some(install_id) = &device.install_id {
+                println!("安装ID: {}", install_id);
+            }
+            println!("注册时间: {}", device.registered_at.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S"));
+
+            match api.get_device_status(&device.device_id).await {
+                Ok(response) => {
+                    let status = response.data.as_ref();
+                    let valid = match status.and_then(|s| s.valid) {
+                        Some(true) => "可用",
+                        Some(false) => "已失效",
+                        None => "未知",
+                    };
+                    println!("设备状态: {}", valid);
+                    if let Some(message) = status.and_then(|s| s.message.as_deref()) {
+                        println!("说明: {}", message);
+                    }
+                }
+                Err(e) => println!("设备状态: 查询失败 ({})", e),
+            }
+        }
+        None => println!("尚未注册设备，运行 fqdl device register 注册"),
+    }
+
+    match api.get_device_pool().await {
+        Ok(response) => {
+            if let Some(pool) = response.data {
+                let count = |n: Option<i64>| n.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string());
+                println!("设备池: 可用 {} / 共 {}", count(pool.available), count(pool.total));
+            }
+        }
+        Err(e) => println!("设备池: 查询失败 ({})", e),
+    }
+
+    Ok(())
+}
+
+async fn cmd_device_register(client: &FanqieClient, force: bool) -> crate::error::Result<()> {
+    let api = client.api();
+
+    if !force {
+        if let Some(device) = api.device().await {
+            println!("已注册设备: {} (使用 --force 重新注册)", device.device_id);
+            return Ok(());
+        }
+    }
+
+    let device = api.reregister_device().await?;
+    println!("注册成功，设备ID: {}", device.device_id);
+    println!("设备信息保存在: {}", DeviceIdentity::default_path().display());
+
+    Ok(())
+}
+
+fn cmd_config_init(force: bool) -> crate::error::Result<()> {
+    let dir = user_config_dir()
+        .ok_or_else(|| FanqieError::ConfigLoad("无法确定用户配置目录".to_string()))?;
+
+    let written = init_config_dir(&dir, force)?;
+    for path in &written {
+        println!("已写入: {}", path.display());
+    }
+    if written.len() < 2 {
+        println!("已存在的文件未被覆盖，使用 --force 覆盖");
+    }
+
+    Ok(())
+}
+
+fn cmd_config_validate(path: Option<PathBuf>) -> crate::error::Result<()> {
+    let path = resolve_config_path(path.as_deref())?;
+    let mut total = 0;
+
+    println!("检查配置文件: {}", path.display());
+    let issues = validate_config_file(&path)?;
+    print_issues(&issues);
+    total += issues.len();
+
+    if let Some(user_path) = UserConfig::default_path().filter(|p| p.is_file()) {
+        println!("\n检查用户配置: {}", user_path.display());
+        let issues = validate_user_config(&user_path)?;
+        print_issues(&issues);
+        total += issues.len();
+    }
 
-    batch_download(options).await?;
+    if total > 0 {
+        return Err(FanqieError::ConfigLoad(format!("配置中发现 {} 个问题", total)));
+    }
 
     Ok(())
 }
 
-async fn cmd_config(config_file: Option<String>) -> crate::error::Result<()> {
-    let config = crate::config::get_config().await;
+fn print_issues(issues: &[ConfigIssue]) {
+    if issues.is_empty() {
+        println!("  ✓ 未发现问题");
+    }
+    for issue in issues {
+        println!("  ✗ {}", issue);
+    }
+}
+
+async fn cmd_config(client: &FanqieClient, settings: &Settings) -> crate::error::Result<()> {
+    let config = client.config();
     let config_guard = config.read().await;
 
     println!("\n当前配置:");
     println!("{}", "=".repeat(50));
     println!("API 节点数量: {}", config_guard.api_sources.len());
+    for source in &config_guard.api_sources {
+        if let Some(proxy) = &source.proxy {
+            println!("  {} 代理: {}", source.base_url, redact_proxy(proxy));
+        }
+        if let Some(auth) = &source.auth {
+            let kind = match auth {
+                NodeAuth::Bearer { .. } => "Bearer".to_string(),
+                NodeAuth::Query { param, .. } => format!("查询参数 {}", param),
+            };
+            println!("  {} 认证: {}", source.base_url, kind);
+        }
+    }
     
     if let Some(node) = config_guard.get_current_node() {
         println!("当前节点: {}", node.base_url);
     }
     
-    println!("最大并发数: {}", config_guard.params.max_workers);
-    println!("API 速率限制: {}", config_guard.params.api_rate_limit);
+    println!("API 速率限制: {} 次/{}秒", config_guard.params.api_rate_limit, config_guard.params.rate_limit_window);
+    println!("节点请求间隔: {}秒", config_guard.params.request_rate_limit);
     println!("请求超时: {}秒", config_guard.params.request_timeout);
     println!("连接池大小: {}", config_guard.params.connection_pool_size);
+    let user_agents = match config_guard.params.user_agents.len() {
+        0 => "内置".to_string(),
+        n => format!("{} 个", n),
+    };
+    let rotation = match config_guard.params.user_agent_rotation {
+        UserAgentRotation::PerClient => "每个节点固定",
+        UserAgentRotation::PerRequest => "每次请求轮换",
+    };
+    println!("User-Agent: {} ({})", user_agents, rotation);
+    println!("{}", "=".repeat(50));
+
+    println!("\n配置文件路径: {}", config_guard.config_path.display());
+
+    println!("\n用户设置:");
+    println!("{}", "=".repeat(50));
+    println!("保存路径: {}  [{}]", settings.save_path.value, settings.save_path.source);
+    println!("导出格式: {}  [{}]", settings.format.value, settings.format.source);
+    println!("下载并发数: {}  [{}]", settings.concurrency.value, settings.concurrency.source);
+    println!("文件名模板: {}  [{}]", settings.filename_template.value, settings.filename_template.source);
+    println!("代理: {}  [{}]",
+        settings.proxy.value.as_deref().map(redact_proxy).unwrap_or_else(|| "无".to_string()),
+        settings.proxy.source);
+    if !settings.no_proxy.value.is_empty() {
+        println!("不使用代理: {}  [{}]", settings.no_proxy.value.join(", "), settings.no_proxy.source);
+    }
     println!("{}", "=".repeat(50));
 
-    if let Some(path) = config_file {
-        println!("\n配置文件路径: {}", path);
-    } else if let Some(path) = find_config_file() {
-        println!("\n配置文件路径: {}", path.display());
+    match &settings.user_config_path {
+        Some(path) => println!("\n用户配置文件: {}", path.display()),
+        None => {
+            if let Some(path) = UserConfig::default_path() {
+                println!("\n用户配置文件: {} (不存在)", path.display());
+            }
+        }
     }
 
     Ok(())
@@ -265,3 +690,11 @@ fn expand_tilde(path: &str) -> String {
     }
     path.to_string()
 }
+
+/// 展开 `~` 并转为绝对路径，使清单中记录的路径在其他目录执行 `update` 时仍然有效
+fn absolute_path(path: &str) -> String {
+    let path = expand_tilde(path);
+    std::path::absolute(&path)
+        .map(|absolute| absolute.to_string_lossy().to_string())
+        .unwrap_or(path)
+}
@@ -0,0 +1,138 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::api::{ApiClient, BookInfo};
+use crate::batch::{BatchDownloader, BatchOptions};
+use crate::config::{load_app_config, load_config_from_file, ApiSource, AppConfig, ConfigParams};
+use crate::downloader::{DownloadOptions, Downloader};
+use crate::error::{FanqieError, Result};
+use crate::probe::{NodeProber, ProbeOptions};
+use crate::search::{get_book_info_with, search_with, SearchResult};
+use crate::update::{UpdateOptions, Updater};
+
+/// 持有独立配置和 API 客户端的句柄，可同时创建多个使用不同节点列表的实例
+#[derive(Clone)]
+pub struct FanqieClient {
+    api: Arc<ApiClient>,
+}
+
+impl FanqieClient {
+    pub fn builder() -> FanqieClientBuilder {
+        FanqieClientBuilder::default()
+    }
+
+    pub fn from_config(config: AppConfig) -> Result<Self> {
+        Ok(Self {
+            api: Arc::new(ApiClient::new(config)?),
+        })
+    }
+
+    pub fn from_api_client(api: Arc<ApiClient>) -> Self {
+        Self { api }
+    }
+
+    pub fn api(&self) -> Arc<ApiClient> {
+        self.api.clone()
+    }
+
+    pub fn config(&self) -> Arc<RwLock<AppConfig>> {
+        self.api.config()
+    }
+
+    pub async fn search(&self, keyword: &str, offset: Option<i32>) -> Result<SearchResult> {
+        search_with(&self.api, keyword, offset).await
+    }
+
+    pub async fn book_info(&self, book_id: &str) -> Result<BookInfo> {
+        get_book_info_with(&self.api, book_id).await
+    }
+
+    pub fn downloader(&self, options: DownloadOptions) -> Downloader {
+        Downloader::new(self.api.clone(), options)
+    }
+
+    pub fn batch(&self, options: BatchOptions) -> BatchDownloader {
+        BatchDownloader::new(self.api.clone(), options)
+    }
+
+    pub fn updater(&self, options: UpdateOptions) -> Updater {
+        Updater::new(self.api.clone(), options)
+    }
+
+    pub fn prober(&self, options: ProbeOptions) -> NodeProber {
+        NodeProber::new(self.api.clone(), options)
+    }
+}
+
+#[derive(Default)]
+pub struct FanqieClientBuilder {
+    config: Option<AppConfig>,
+    config_file: Option<PathBuf>,
+    api_sources: Option<Vec<ApiSource>>,
+    params: Option<ConfigParams>,
+}
+
+impl FanqieClientBuilder {
+    /// 使用已构建好的配置
+    pub fn config(mut self, config: AppConfig) -> Self {
+        self.config = Some(config);
+        self
+    }
+
+    /// 从指定的 fanqie.json 加载配置
+    pub fn config_file<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.config_file = Some(path.into());
+        self
+    }
+
+    /// 覆盖配置中的节点列表
+    pub fn api_sources(mut self, sources: Vec<ApiSource>) -> Self {
+        self.api_sources = Some(sources);
+        self
+    }
+
+    /// 按地址覆盖配置中的节点列表，节点的其余选项使用默认值
+    pub fn nodes<I, S>(self, urls: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let sources = urls.into_iter().map(|url| ApiSource::new(url)).collect();
+        self.api_sources(sources)
+    }
+
+    /// 覆盖配置中的下载参数
+    pub fn params(mut self, params: ConfigParams) -> Self {
+        self.params = Some(params);
+        self
+    }
+
+    /// 未提供配置或配置文件时，节点列表必须通过 `nodes`/`api_sources` 指定，
+    /// 否则从默认位置查找 fanqie.json
+    pub fn build(self) -> Result<FanqieClient> {
+        let mut config = match (self.config, self.config_file) {
+            (Some(config), _) => config,
+            (None, Some(path)) => {
+                let json = load_config_from_file(&path)?;
+                AppConfig::new(json, path)
+            }
+            (None, None) if self.api_sources.is_some() => AppConfig::default(),
+            (None, None) => load_app_config()?,
+        };
+
+        if let Some(sources) = self.api_sources {
+            config.api_sources = sources;
+            config.current_node_index = 0;
+        }
+        if let Some(params) = self.params {
+            config.params = params;
+        }
+
+        if config.api_sources.is_empty() {
+            return Err(FanqieError::ConfigLoad("未配置任何 API 节点".to_string()));
+        }
+
+        FanqieClient::from_config(config)
+    }
+}
@@ -0,0 +1,48 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::state_dir;
+use crate::error::{FanqieError, Result};
+
+/// 通过 `ios_register` 注册得到的设备身份，`ios_content` 接口需要携带设备ID
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeviceIdentity {
+    pub device_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub install_id: Option<String>,
+    pub registered_at: DateTime<Utc>,
+}
+
+impl DeviceIdentity {
+    pub fn new(device_id: String, install_id: Option<String>) -> Self {
+        Self {
+            device_id,
+            install_id,
+            registered_at: Utc::now(),
+        }
+    }
+
+    pub fn default_path() -> PathBuf {
+        state_dir().join("device.json")
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path.as_ref())?;
+        serde_json::from_str(&content)
+            .map_err(|e| FanqieError::JsonParse(format!("设备信息文件格式错误: {}", e)))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| FanqieError::FileWrite(format!("创建目录失败: {}", e)))?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| FanqieError::JsonParse(format!("序列化设备信息失败: {}", e)))?;
+        fs::write(path.as_ref(), json)
+            .map_err(|e| FanqieError::FileWrite(format!("写入设备信息失败: {}", e)))
+    }
+}
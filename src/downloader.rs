@@ -1,20 +1,56 @@
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use tokio::sync::mpsc;
-use futures::future::join_all;
+use futures::stream::{self, StreamExt};
 use indicatif::{ProgressBar, ProgressStyle};
 
-use crate::api::{get_api_client, ChapterContent, ChapterInfo, ChapterInfoRaw};
-use crate::config::get_config;
+use crate::api::{get_api_client, ApiClient, BookInfo, ChapterContent, ChapterInfo};
+use crate::cache::ChapterCache;
+use crate::device::DeviceIdentity;
 use crate::error::{FanqieError, Result};
-use crate::export::{export_txt, export_epub, ensure_output_dir};
-
-#[derive(Debug, Clone)]
+use crate::export::{
+    export_dir, export_epub, export_html, export_markdown, export_txt, ensure_output_dir, output_dir, output_path,
+    EpubOptions, EpubTheme, EXPORT_FORMATS, EXPORT_LAYOUTS,
+};
+use crate::manifest::DownloadManifest;
+use crate::settings::DEFAULT_FILENAME_TEMPLATE;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DownloadOptions {
     pub book_id: String,
     pub save_path: String,
     pub format: String,
     pub start_chapter: Option<usize>,
     pub end_chapter: Option<usize>,
+    pub use_cache: bool,
+    pub allow_partial: bool,
+    pub full_download: bool,
+    /// 导出文件名模板，未设置时使用书名
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filename_template: Option<String>,
+    /// EPUB 样式主题，见 [`EpubTheme::NAMES`]，未设置时为 `default`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epub_theme: Option<String>,
+    /// 追加到 EPUB 样式表末尾的 CSS 文件
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epub_css: Option<String>,
+    /// 导出布局，见 [`EXPORT_LAYOUTS`]，未设置时为 `single`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+}
+
+impl DownloadOptions {
+    pub fn filename_template(&self) -> &str {
+        self.filename_template.as_deref().unwrap_or(DEFAULT_FILENAME_TEMPLATE)
+    }
+
+    /// 是否按章节分文件导出到目录
+    pub fn dir_layout(&self) -> bool {
+        self.layout.as_deref().is_some_and(|layout| layout.trim().eq_ignore_ascii_case("dir"))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -25,43 +61,128 @@ pub enum DownloadProgress {
     Error { message: String },
 }
 
+/// 按原始顺序排列的章节内容，下载失败的章节以占位内容填充并记录在 `missing` 中
+#[derive(Debug, Clone)]
+pub struct DownloadedChapters {
+    pub contents: Vec<ChapterContent>,
+    pub missing: Vec<ChapterInfo>,
+}
+
+/// 首轮下载结束后，对失败章节额外进行的重试轮数
+const FINAL_RETRY_PASSES: usize = 1;
+
+fn missing_placeholder(chapter: &ChapterInfo) -> ChapterContent {
+    ChapterContent {
+        chapter_id: chapter.chapter_id.clone(),
+        title: chapter.title.clone(),
+        content: placeholder_text(&chapter.chapter_id),
+        volume_name: chapter.volume_name.clone(),
+    }
+}
+
+/// 缺失章节在导出文件中的占位内容
+pub(crate) fn placeholder_text(chapter_id: &str) -> String {
+    format!("【本章下载失败，内容缺失 (章节ID: {})】", chapter_id)
+}
+
+/// 检查章节范围 (从 0 开始、左闭右开) 非空且不超过章节总数
+fn check_range(range: &Range<usize>, total: usize) -> Result<()> {
+    if range.start >= total {
+        return Err(FanqieError::Download(format!("起始章节超出范围 (共 {} 章)", total)));
+    }
+    if range.end <= range.start || range.end > total {
+        return Err(FanqieError::Download(format!(
+            "章节范围无效: 第 {} 章至第 {} 章 (共 {} 章)",
+            range.start + 1,
+            range.end,
+            total
+        )));
+    }
+    Ok(())
+}
+
+fn content_from_response(chapter_id: &str, data: Option<ChapterContent>) -> Result<ChapterContent> {
+    let mut content = data
+        .filter(|content| !content.content.trim().is_empty())
+        .ok_or_else(|| FanqieError::ChapterFetch(format!("章节内容为空: {}", chapter_id)))?;
+    if content.chapter_id.is_empty() {
+        content.chapter_id = chapter_id.to_string();
+    }
+
+    Ok(content)
+}
+
+/// iOS 正文接口拒绝了当前设备 (无权访问或明确的设备无效错误码)，内容和解析错误不算
+fn is_device_rejection(error: &FanqieError) -> bool {
+    matches!(error, FanqieError::Forbidden(_) | FanqieError::DeviceInvalid(_))
+}
+
+/// `/api/content` 本身失败 (超时、连接失败、5xx 或正文为空) 时才改用 iOS 接口，
+/// 限流、不存在、付费锁定等明确的拒绝原样返回
+fn should_try_ios(error: &FanqieError) -> bool {
+    matches!(
+        error,
+        FanqieError::Timeout
+            | FanqieError::Network(_)
+            | FanqieError::ApiRequest(_)
+            | FanqieError::ApiNodeUnavailable(_)
+            | FanqieError::AllNodesUnavailable
+            | FanqieError::ChapterFetch(_)
+    )
+}
+
 pub struct Downloader {
+    client: Arc<ApiClient>,
     options: DownloadOptions,
+    cache: Option<ChapterCache>,
 }
 
 impl Downloader {
-    pub fn new(options: DownloadOptions) -> Self {
-        Self { options }
+    pub fn new(client: Arc<ApiClient>, options: DownloadOptions) -> Self {
+        Self::with_cache_root(client, options, ChapterCache::default_root())
+    }
+
+    /// 章节缓存保存在 `cache_root` 下而不是默认的用户缓存目录
+    pub fn with_cache_root<P: AsRef<Path>>(client: Arc<ApiClient>, options: DownloadOptions, cache_root: P) -> Self {
+        let cache = options.use_cache
+            .then(|| ChapterCache::new(cache_root, &options.book_id));
+        Self { client, options, cache }
+    }
+
+    pub fn client(&self) -> &Arc<ApiClient> {
+        &self.client
     }
 
     pub async fn get_chapters(&self) -> Result<Vec<ChapterInfo>> {
-        let client = get_api_client();
-        
-        let directory_response = client.get_directory(&self.options.book_id).await?;
+        let client = &self.client;
         
-        if directory_response.code == 200 {
-            if let Some(data) = directory_response.data {
-                if let Some(lists) = data.lists {
-                    return Ok(lists);
+        // 目录接口失败时改用章节列表接口
+        if let Ok(directory_response) = client.get_directory(&self.options.book_id).await {
+            if let Some(mut lists) = directory_response.data.and_then(|data| data.lists) {
+                for chapter in &mut lists {
+                    chapter.volume_name = chapter.volume_name.take().filter(|name| !name.trim().is_empty());
                 }
+                return Ok(lists);
             }
         }
 
         let chapter_response = client.get_chapter_list(&self.options.book_id).await?;
-        
-        if chapter_response.code != 200 {
-            return Err(FanqieError::ChapterFetch(
-                format!("获取章节列表失败: API 返回错误码 {}", chapter_response.code)
-            ));
-        }
 
         if let Some(wrapper) = chapter_response.data {
             if let Some(data) = wrapper.data {
                 if let Some(volumes) = data.chapter_list_with_volume {
+                    // 接口未给出卷名时按分组顺序命名，只有一组时视为不分卷
+                    let named = volumes.len() > 1;
                     let chapters: Vec<ChapterInfo> = volumes
                         .iter()
-                        .flat_map(|v| v.iter())
-                        .map(|c| c.to_chapter_info())
+                        .enumerate()
+                        .flat_map(|(index, volume)| volume.iter().map(move |c| {
+                            let mut chapter = c.to_chapter_info();
+                            if chapter.volume_name.as_deref().is_none_or(|name| name.trim().is_empty()) {
+                                chapter.volume_name = named.then(|| format!("第{}卷", index + 1));
+                            }
+                            chapter
+                        }))
                         .collect();
                     if !chapters.is_empty() {
                         return Ok(chapters);
@@ -76,32 +197,83 @@ impl Downloader {
         Err(FanqieError::ChapterFetch("章节列表为空".to_string()))
     }
 
+    /// 下载章节正文，`content` 接口失败时改用 iOS 接口，两者都失败时返回 `content` 的错误
     pub async fn download_chapter(&self, chapter_id: &str) -> Result<ChapterContent> {
-        let client = get_api_client();
-        let response = client.get_chapter_content(chapter_id).await?;
+        let client = &self.client;
+        let primary = match client.get_chapter_content(chapter_id).await {
+            Ok(response) => content_from_response(chapter_id, response.data),
+            Err(e) => Err(e),
+        };
+
+        match primary {
+            Ok(content) => Ok(content),
+            Err(e) if should_try_ios(&e) => self.download_chapter_ios(chapter_id).await.map_err(|ios_error| {
+                log::debug!("iOS 正文接口获取章节 {} 失败: {}", chapter_id, ios_error);
+                e
+            }),
+            Err(e) => Err(e),
+        }
+    }
 
-        if response.code != 200 {
-            return Err(FanqieError::ChapterFetch(
-                format!("获取章节内容失败: {}", chapter_id)
-            ));
+    async fn download_chapter_ios(&self, chapter_id: &str) -> Result<ChapterContent> {
+        let client = &self.client;
+        let device = client.ensure_device().await?;
+        match self.fetch_ios_content(chapter_id, &device).await {
+            // 设备ID失效 (如恢复的设备已被服务端注销) 时重新注册一次后重试
+            Err(e) if is_device_rejection(&e) => {
+                log::debug!("设备 {} 被 iOS 正文接口拒绝，重新注册: {}", device.device_id, e);
+                let device = client.renew_device(&device).await?;
+                self.fetch_ios_content(chapter_id, &device).await
+            }
+            result => result,
         }
+    }
 
-        response.data.ok_or_else(|| {
-            FanqieError::ChapterFetch(format!("章节内容为空: {}", chapter_id))
-        })
+    async fn fetch_ios_content(&self, chapter_id: &str, device: &DeviceIdentity) -> Result<ChapterContent> {
+        let response = self.client.get_ios_content(chapter_id, &device.device_id).await?;
+        content_from_response(chapter_id, response.data)
+    }
+
+    /// 优先从缓存读取章节，缓存未命中时下载并写入缓存
+    async fn fetch_chapter(&self, chapter: &ChapterInfo) -> Result<ChapterContent> {
+        if let Some(cache) = &self.cache {
+            if let Some(content) = cache.load(&chapter.chapter_id) {
+                return Ok(content);
+            }
+        }
+
+        let content = self.download_chapter(&chapter.chapter_id).await?;
+
+        if let Some(cache) = &self.cache {
+            if let Err(e) = cache.store(&content) {
+                eprintln!("警告: {}", e);
+            }
+        }
+
+        Ok(content)
     }
 
     pub async fn download_all_chapters(
         &self,
         chapters: &[ChapterInfo],
         progress_tx: Option<mpsc::Sender<DownloadProgress>>,
-    ) -> Result<Vec<ChapterContent>> {
-        let config = get_config().await;
+    ) -> Result<DownloadedChapters> {
+        let config = self.client.config();
         let config_guard = config.read().await;
-        let max_workers = config_guard.params.max_workers;
+        let max_workers = config_guard.params.workers();
         drop(config_guard);
 
         let total = chapters.len();
+
+        if let Some(cache) = &self.cache {
+            let cached = chapters
+                .iter()
+                .filter(|c| cache.contains(&c.chapter_id))
+                .count();
+            if cached > 0 {
+                println!("已缓存 {} 章，将从断点继续下载", cached);
+            }
+        }
         
         if let Some(tx) = &progress_tx {
             tx.send(DownloadProgress::Started { total }).await.ok();
@@ -113,64 +285,304 @@ impl Downloader {
             .unwrap()
             .progress_chars("#>-"));
 
-        let mut results = Vec::with_capacity(total);
-        let chunks: Vec<Vec<ChapterInfo>> = chapters
-            .chunks(max_workers)
-            .map(|c| c.to_vec())
-            .collect();
+        let mut slots: Vec<Option<ChapterContent>> = vec![None; total];
+        let mut pending: Vec<usize> = (0..total).collect();
 
-        for (chunk_index, chunk) in chunks.iter().enumerate() {
-            let futures: Vec<_> = chunk
-                .iter()
-                .map(|chapter| self.download_chapter(&chapter.chapter_id))
-                .collect();
-
-            let chunk_results = join_all(futures).await;
-
-            for (i, result) in chunk_results.into_iter().enumerate() {
-                let current = chunk_index * max_workers + i + 1;
-                
-                match result {
-                    Ok(content) => {
-                        if let Some(tx) = &progress_tx {
-                            tx.send(DownloadProgress::Chapter {
-                                current,
-                                total,
-                                title: content.title.clone(),
-                            }).await.ok();
+        for pass in 0..=FINAL_RETRY_PASSES {
+            if pending.is_empty() {
+                break;
+            }
+            if pass > 0 {
+                pb.println(format!("重试 {} 个下载失败的章节", pending.len()));
+            }
+
+            let mut failed = Vec::new();
+
+            // 始终保持 max_workers 个请求在途，先完成的结果暂存，按章节顺序依次产出
+            let order = std::mem::take(&mut pending);
+            let mut completed: BTreeMap<usize, Result<ChapterContent>> = BTreeMap::new();
+            let mut next = 0;
+
+            let mut results = stream::iter(order.iter().copied().enumerate())
+                .map(|(position, index)| async move {
+                    (position, self.fetch_chapter(&chapters[index]).await)
+                })
+                .buffer_unordered(max_workers.max(1));
+
+            while let Some((position, result)) = results.next().await {
+                if result.is_ok() {
+                    pb.inc(1);
+                }
+                completed.insert(position, result);
+
+                while let Some(result) = completed.remove(&next) {
+                    let index = order[next];
+                    next += 1;
+
+                    match result {
+                        Ok(content) => {
+                            if let Some(tx) = &progress_tx {
+                                tx.send(DownloadProgress::Chapter {
+                                    current: index + 1,
+                                    total,
+                                    title: content.title.clone(),
+                                }).await.ok();
+                            }
+                            slots[index] = Some(content);
                         }
-                        results.push(content);
-                        pb.inc(1);
-                    }
-                    Err(e) => {
-                        if let Some(tx) = &progress_tx {
-                            tx.send(DownloadProgress::Error {
-                                message: format!("章节下载失败: {}", e),
-                            }).await.ok();
+                        Err(e) => {
+                            if let Some(tx) = &progress_tx {
+                                tx.send(DownloadProgress::Error {
+                                    message: format!("章节下载失败: {}", e),
+                                }).await.ok();
+                            }
+                            failed.push(index);
                         }
                     }
                 }
             }
+
+            pending = failed;
         }
 
         pb.finish_with_message("下载完成");
-        Ok(results)
+
+        let mut result = DownloadedChapters {
+            contents: Vec::with_capacity(total),
+            missing: Vec::new(),
+        };
+
+        for (chapter, slot) in chapters.iter().zip(slots) {
+            match slot {
+                Some(mut content) => {
+                    content.volume_name = chapter.volume_name.clone();
+                    result.contents.push(content);
+                }
+                None => {
+                    result.contents.push(missing_placeholder(chapter));
+                    result.missing.push(chapter.clone());
+                }
+            }
+        }
+
+        Ok(result)
     }
 
-    pub async fn download_book(&self) -> Result<PathBuf> {
-        let client = get_api_client();
+    /// 通过 `raw_full` 接口一次获取整本书，并截取 `range` 范围内的章节。
+    /// 整本接口不可用或返回的章节不完整时，缺少的章节改为逐章下载
+    pub async fn download_full(
+        &self,
+        chapters: &[ChapterInfo],
+        range: Range<usize>,
+    ) -> Result<DownloadedChapters> {
+        check_range(&range, chapters.len())?;
+        let selected = &chapters[range];
+        let client = &self.client;
+
+        println!("正在尝试整本下载...");
+
+        let mut fetched: HashMap<String, ChapterContent> = match client.get_raw_full(&self.options.book_id).await {
+            Ok(response) => {
+                response.data
+                    .and_then(|d| d.chapters)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(|(position, mut content)| {
+                        // 整本接口未返回章节ID时，按目录顺序对应
+                        if content.chapter_id.is_empty() {
+                            content.chapter_id = chapters.get(position)?.chapter_id.clone();
+                        }
+                        // 正文为空的章节交给逐章下载重试
+                        if content.content.trim().is_empty() {
+                            return None;
+                        }
+                        Some((content.chapter_id.clone(), content))
+                    })
+                    .collect()
+            }
+            Err(e) => {
+                println!("整本下载失败: {}", e);
+                HashMap::new()
+            }
+        };
+
+        if let Some(cache) = &self.cache {
+            for chapter in selected {
+                if let Some(content) = fetched.get(&chapter.chapter_id) {
+                    if let Err(e) = cache.store(content) {
+                        eprintln!("警告: {}", e);
+                    }
+                }
+            }
+        }
+
+        let remaining: Vec<ChapterInfo> = selected
+            .iter()
+            .filter(|c| !fetched.contains_key(&c.chapter_id))
+            .cloned()
+            .collect();
+
+        let mut missing = Vec::new();
+
+        if !remaining.is_empty() {
+            println!("整本下载缺少 {} 章，改为逐章下载", remaining.len());
+            let partial = self.download_all_chapters(&remaining, None).await?;
+            missing = partial.missing;
+            for content in partial.contents {
+                fetched.insert(content.chapter_id.clone(), content);
+            }
+        }
+
+        let contents = selected
+            .iter()
+            .map(|chapter| match fetched.remove(&chapter.chapter_id) {
+                Some(mut content) => {
+                    content.volume_name = chapter.volume_name.clone();
+                    content
+                }
+                None => missing_placeholder(chapter),
+            })
+            .collect();
+
+        Ok(DownloadedChapters { contents, missing })
+    }
+
+    pub async fn get_book_info(&self) -> Result<BookInfo> {
+        let client = &self.client;
         
         let detail_response = client.get_book_detail(&self.options.book_id).await?;
-        
-        if detail_response.code != 200 {
-            return Err(FanqieError::BookNotFound(self.options.book_id.clone()));
-        }
 
-        let book_info = detail_response.data
+        detail_response.data
             .and_then(|d| d.data)
             .ok_or_else(|| {
                 FanqieError::BookNotFound(self.options.book_id.clone())
-            })?;
+            })
+    }
+
+    /// 小写的导出格式，格式不在 [`EXPORT_FORMATS`] 中或与布局不匹配时返回错误
+    fn export_format(&self) -> Result<String> {
+        let format = self.options.format.trim().to_lowercase();
+        if !EXPORT_FORMATS.contains(&format.as_str()) {
+            return Err(FanqieError::UnsupportedFormat(format!(
+                "{} (可选: {})",
+                self.options.format,
+                EXPORT_FORMATS.join(", ")
+            )));
+        }
+
+        if let Some(layout) = &self.options.layout {
+            if !EXPORT_LAYOUTS.contains(&layout.trim().to_lowercase().as_str()) {
+                return Err(FanqieError::UnsupportedFormat(format!(
+                    "未知的导出布局 {} (可选: {})",
+                    layout,
+                    EXPORT_LAYOUTS.join(", ")
+                )));
+            }
+        }
+        if self.options.dir_layout() && !matches!(format.as_str(), "txt" | "md") {
+            return Err(FanqieError::UnsupportedFormat(format!("{} 不支持按章节分文件，dir 布局仅支持 txt 和 md", format)));
+        }
+
+        Ok(format)
+    }
+
+    /// 解析 EPUB 主题并读取自定义样式文件
+    fn epub_style(&self) -> Result<(EpubTheme, Option<String>)> {
+        let theme = match self.options.epub_theme.as_deref() {
+            Some(name) => EpubTheme::from_name(name).ok_or_else(|| {
+                FanqieError::EpubGeneration(format!(
+                    "未知的 EPUB 主题: {} (可选: {})",
+                    name,
+                    EpubTheme::NAMES.join(", ")
+                ))
+            })?,
+            None => EpubTheme::default(),
+        };
+
+        let custom_css = match self.options.epub_css.as_deref() {
+            Some(path) => Some(std::fs::read_to_string(path).map_err(|e| {
+                FanqieError::EpubGeneration(format!("无法读取样式文件 {}: {}", path, e))
+            })?),
+            None => None,
+        };
+
+        Ok((theme, custom_css))
+    }
+
+    /// 准备 EPUB 导出选项: 解析样式并下载封面图片，封面下载失败时仅提示并继续导出
+    pub async fn epub_options(&self, book_info: &BookInfo) -> Result<EpubOptions> {
+        let (theme, custom_css) = self.epub_style()?;
+
+        let cover = match book_info.cover.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(url) => match self.client.fetch_cover(url).await {
+                Ok(cover) => Some(cover),
+                Err(e) => {
+                    eprintln!("警告: 封面下载失败，将不带封面导出: {}", e);
+                    None
+                }
+            },
+        };
+
+        Ok(EpubOptions { cover, theme, custom_css })
+    }
+
+    /// 按 `format` 导出文件，并在旁边写入清单文件
+    pub async fn export(&self, book_info: &BookInfo, downloaded: &DownloadedChapters) -> Result<PathBuf> {
+        let contents = &downloaded.contents;
+        let template = self.options.filename_template();
+        let format = self.export_format()?;
+        let output_path = if self.options.dir_layout() {
+            let dir = output_dir(&self.options.save_path, book_info, template);
+            export_dir(book_info, contents, &dir, &format, 0)?;
+            dir
+        } else {
+            let path = output_path(&self.options.save_path, book_info, template, &format);
+            match format.as_str() {
+                "epub" => export_epub(book_info, contents, &path, &self.epub_options(book_info).await?)?,
+                "html" => export_html(book_info, contents, &path)?,
+                "md" => export_markdown(book_info, contents, &path)?,
+                _ => export_txt(book_info, contents, &path)?,
+            }
+            path
+        };
+
+        let source_node = self.client.get_current_node().await;
+        let mut manifest = DownloadManifest::new(book_info, &self.options, &output_path, source_node);
+        manifest.append_chapters(contents, &downloaded.missing);
+        manifest.save(&output_path)?;
+
+        Ok(output_path)
+    }
+
+    /// 输出缺失章节汇总，未指定 `allow_partial` 时返回错误
+    pub fn report_missing(&self, missing: &[ChapterInfo]) -> Result<()> {
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        println!("\n{} 个章节下载失败:", missing.len());
+        for chapter in missing {
+            println!("  {} {}", chapter.chapter_id, chapter.title);
+        }
+
+        if self.options.allow_partial {
+            Ok(())
+        } else {
+            Err(FanqieError::IncompleteDownload(missing.len()))
+        }
+    }
+
+    pub fn clear_cache(&self) -> Result<()> {
+        match &self.cache {
+            Some(cache) => cache.clear(),
+            None => Ok(()),
+        }
+    }
+
+    pub async fn download_book(&self) -> Result<PathBuf> {
+        let book_info = self.get_book_info().await?;
 
         println!("正在下载: {}", book_info.book_name);
         println!("作者: {}", book_info.author);
@@ -179,18 +591,14 @@ impl Downloader {
         let total_chapters = chapters.len();
         println!("共 {} 章", total_chapters);
 
-        let selected_chapters: Vec<ChapterInfo> = {
-            let start = self.options.start_chapter.unwrap_or(1).max(1) - 1;
-            let end = self.options.end_chapter
-                .unwrap_or(total_chapters)
-                .min(total_chapters);
-            
-            if start >= total_chapters {
-                return Err(FanqieError::Download("起始章节超出范围".to_string()));
-            }
+        let start = self.options.start_chapter.unwrap_or(1).max(1) - 1;
+        let end = self.options.end_chapter
+            .unwrap_or(total_chapters)
+            .min(total_chapters);
 
-            chapters[start..end].to_vec()
-        };
+        check_range(&(start..end), total_chapters)?;
+
+        let selected_chapters: Vec<ChapterInfo> = chapters[start..end].to_vec();
 
         println!("下载范围: {} - {}", 
             selected_chapters.first().map(|c| c.title.as_str()).unwrap_or(""),
@@ -198,22 +606,34 @@ impl Downloader {
         );
 
         ensure_output_dir(&self.options.save_path)?;
+        // 格式或样式设置有误时在下载章节前报错
+        if self.export_format()? == "epub" {
+            self.epub_style()?;
+        }
 
-        let contents = self.download_all_chapters(&selected_chapters, None).await?;
-
-        let output_path = match self.options.format.to_lowercase().as_str() {
-            "txt" => export_txt(&book_info, &contents, &self.options.save_path)?,
-            "epub" => export_epub(&book_info, &contents, &self.options.save_path)?,
-            _ => export_txt(&book_info, &contents, &self.options.save_path)?,
+        let downloaded = if self.options.full_download {
+            self.download_full(&chapters, start..end).await?
+        } else {
+            self.download_all_chapters(&selected_chapters, None).await?
         };
 
+        let output_path = self.export(&book_info, &downloaded).await?;
+
         println!("保存至: {}", output_path.display());
+        if !downloaded.missing.is_empty() {
+            println!("缺失章节已在导出文件中以占位内容标记");
+        }
+
+        self.report_missing(&downloaded.missing)?;
+        if downloaded.missing.is_empty() {
+            self.clear_cache()?;
+        }
 
         Ok(output_path)
     }
 }
 
 pub async fn download_book(options: DownloadOptions) -> Result<PathBuf> {
-    let downloader = Downloader::new(options);
+    let downloader = Downloader::new(get_api_client()?, options);
     downloader.download_book().await
 }
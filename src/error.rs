@@ -20,18 +20,36 @@ pub enum FanqieError {
     #[error("书籍不存在或已下架: {0}")]
     BookNotFound(String),
 
+    #[error("内容不存在或已下架: {0}")]
+    NotFound(String),
+
+    #[error("内容需要付费或已锁定: {0}")]
+    Locked(String),
+
+    #[error("无权访问: {0}")]
+    Forbidden(String),
+
+    #[error("设备ID无效: {0}")]
+    DeviceInvalid(String),
+
     #[error("章节获取失败: {0}")]
     ChapterFetch(String),
 
     #[error("下载失败: {0}")]
     Download(String),
 
+    #[error("下载不完整: {0} 个章节缺失 (使用 --allow-partial 忽略)")]
+    IncompleteDownload(usize),
+
     #[error("文件写入失败: {0}")]
     FileWrite(String),
 
     #[error("EPUB 生成失败: {0}")]
     EpubGeneration(String),
 
+    #[error("不支持的导出格式: {0}")]
+    UnsupportedFormat(String),
+
     #[error("无效的书籍 ID: {0}")]
     InvalidBookId(String),
 
@@ -50,8 +68,8 @@ pub enum FanqieError {
     #[error("请求超时")]
     Timeout,
 
-    #[error("速率限制")]
-    RateLimited,
+    #[error("请求被限流: {0}")]
+    RateLimited(String),
 }
 
 pub type Result<T> = std::result::Result<T, FanqieError>;
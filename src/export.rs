@@ -1,16 +1,53 @@
-use std::fs::{self, File};
-use std::io::Write;
-use std::path::PathBuf;
-use html_escape::encode_text;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use chrono::{DateTime, Utc};
+use html_escape::{decode_html_entities, encode_double_quoted_attribute, encode_text};
 
 use crate::api::{BookInfo, ChapterContent};
 use crate::error::{FanqieError, Result};
 
-pub fn export_txt(book_info: &BookInfo, chapters: &[ChapterContent], save_path: &str) -> Result<PathBuf> {
-    let file_name = sanitize_filename(&book_info.book_name);
-    let output_path = PathBuf::from(save_path).join(format!("{}.txt", file_name));
+mod dir;
+mod html;
+mod markdown;
 
-    let mut file = File::create(&output_path)
+pub use dir::{chapter_file_name, export_dir};
+pub use html::{export_html, read_html_chapters};
+pub use markdown::{append_markdown, export_markdown};
+
+/// 支持的导出格式
+pub const EXPORT_FORMATS: [&str; 4] = ["txt", "epub", "html", "md"];
+
+/// 导出布局: `single` 为单个文件，`dir` 为每章一个文件 (仅 txt 和 md)
+pub const EXPORT_LAYOUTS: [&str; 2] = ["single", "dir"];
+
+/// 按模板生成不含扩展名的文件名，支持 `{title}`、`{author}` 和 `{book_id}` 占位符
+pub fn render_file_name(book_info: &BookInfo, template: &str) -> String {
+    let name = template
+        .replace("{title}", &book_info.book_name)
+        .replace("{author}", &book_info.author)
+        .replace("{book_id}", &book_info.book_id);
+
+    let name = sanitize_filename(&name);
+    if name.is_empty() {
+        sanitize_filename(&book_info.book_name)
+    } else {
+        name
+    }
+}
+
+/// 导出文件的完整路径: `<save_path>/<按模板生成的文件名>.<extension>`
+pub fn output_path(save_path: &str, book_info: &BookInfo, template: &str, extension: &str) -> PathBuf {
+    PathBuf::from(save_path).join(format!("{}.{}", render_file_name(book_info, template), extension))
+}
+
+/// 按章节分文件导出时的目录: `<save_path>/<按模板生成的文件名>`
+pub fn output_dir(save_path: &str, book_info: &BookInfo, template: &str) -> PathBuf {
+    PathBuf::from(save_path).join(render_file_name(book_info, template))
+}
+
+pub fn export_txt(book_info: &BookInfo, chapters: &[ChapterContent], output_path: &Path) -> Result<()> {
+    let mut file = File::create(output_path)
         .map_err(|e| FanqieError::FileWrite(format!("创建文件失败: {}", e)))?;
 
     writeln!(file, "书名: {}", book_info.book_name)
@@ -20,23 +57,240 @@ pub fn export_txt(book_info: &BookInfo, chapters: &[ChapterContent], save_path:
     writeln!(file, "\n{}\n", "=".repeat(50))
         .map_err(|e| FanqieError::FileWrite(format!("写入失败: {}", e)))?;
 
+    write_txt_chapters(&mut file, chapters, None)
+}
+
+/// 将章节追加到已导出的 TXT 文件末尾，`last_volume` 为文件中最后一章所属的分卷
+pub fn append_txt(path: &Path, chapters: &[ChapterContent], last_volume: Option<&str>) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(path)
+        .map_err(|e| FanqieError::FileWrite(format!("打开文件失败: {}", e)))?;
+
+    write_txt_chapters(&mut file, chapters, last_volume)
+}
+
+/// 从 TXT 或 Markdown 导出文件末尾移除 `chapters` 写入的内容，`last_volume` 为这些章节之前最后一章所属的分卷。
+/// 文件末尾与这些章节不一致时报错，文件保持不变
+pub fn remove_trailing_chapters(
+    path: &Path,
+    format: &str,
+    chapters: &[ChapterContent],
+    last_volume: Option<&str>,
+) -> Result<()> {
+    let mut suffix = Vec::new();
+    match format {
+        "md" => markdown::write_markdown_chapters(&mut suffix, chapters, last_volume)?,
+        _ => write_txt_chapters(&mut suffix, chapters, last_volume)?,
+    }
+
+    let content = fs::read(path)
+        .map_err(|e| FanqieError::FileWrite(format!("读取 {} 失败: {}", path.display(), e)))?;
+    if !content.ends_with(&suffix) {
+        return Err(FanqieError::Download(format!(
+            "{} 末尾的 {} 章与下载记录不一致",
+            path.display(),
+            chapters.len()
+        )));
+    }
+
+    OpenOptions::new()
+        .write(true)
+        .open(path)
+        .and_then(|file| file.set_len((content.len() - suffix.len()) as u64))
+        .map_err(|e| FanqieError::FileWrite(format!("写入 {} 失败: {}", path.display(), e)))
+}
+
+fn write_txt_chapters<W: Write>(file: &mut W, chapters: &[ChapterContent], last_volume: Option<&str>) -> Result<()> {
+    let mut volumes = VolumeTracker::new(last_volume);
     for chapter in chapters {
+        if let Some(volume) = volumes.enter(chapter) {
+            writeln!(file, "\n{}\n{}", volume, "-".repeat(50))
+                .map_err(|e| FanqieError::FileWrite(format!("写入失败: {}", e)))?;
+        }
         writeln!(file, "\n{}\n", chapter.title)
             .map_err(|e| FanqieError::FileWrite(format!("写入失败: {}", e)))?;
         writeln!(file, "{}\n", chapter.content)
             .map_err(|e| FanqieError::FileWrite(format!("写入失败: {}", e)))?;
     }
 
-    Ok(output_path)
+    Ok(())
 }
 
-pub fn export_epub(book_info: &BookInfo, chapters: &[ChapterContent], save_path: &str) -> Result<PathBuf> {
-    let file_name = sanitize_filename(&book_info.book_name);
-    let output_path = PathBuf::from(save_path).join(format!("{}.epub", file_name));
+/// 按顺序遍历章节时记录当前分卷，进入新分卷时返回卷名
+struct VolumeTracker<'a> {
+    current: Option<&'a str>,
+}
 
-    let file = File::create(&output_path)
-        .map_err(|e| FanqieError::FileWrite(format!("创建文件失败: {}", e)))?;
+impl<'a> VolumeTracker<'a> {
+    fn new(current: Option<&'a str>) -> Self {
+        Self { current }
+    }
 
+    fn enter(&mut self, chapter: &'a ChapterContent) -> Option<&'a str> {
+        let volume = chapter.volume_name.as_deref();
+        if volume == self.current {
+            return None;
+        }
+        self.current = volume;
+        volume
+    }
+}
+
+/// EPUB 封面图片
+#[derive(Debug, Clone)]
+pub struct CoverImage {
+    pub data: Vec<u8>,
+    pub mime_type: String,
+}
+
+impl CoverImage {
+    /// 根据文件头识别图片格式，`content_type` 仅在无法识别时使用；不是图片时返回 `None`
+    pub fn from_bytes(data: Vec<u8>, content_type: Option<&str>) -> Option<Self> {
+        let sniffed = if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+            Some("image/jpeg")
+        } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+            Some("image/png")
+        } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+            Some("image/gif")
+        } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            Some("image/webp")
+        } else {
+            None
+        };
+
+        let mime_type = sniffed.map(str::to_string).or_else(|| {
+            content_type
+                .map(|t| t.split(';').next().unwrap_or_default().trim().to_lowercase())
+                .filter(|t| t.starts_with("image/"))
+        })?;
+
+        Some(Self { data, mime_type })
+    }
+
+    fn extension(&self) -> &str {
+        match self.mime_type.as_str() {
+            "image/jpeg" => "jpg",
+            "image/png" => "png",
+            "image/gif" => "gif",
+            "image/webp" => "webp",
+            "image/svg+xml" => "svg",
+            other => other.trim_start_matches("image/"),
+        }
+    }
+}
+
+const ABOUT_TITLE: &str = "关于本书";
+
+/// 所有主题共用的样式
+const BASE_CSS: &str = r#"body { margin: 0 5%; line-height: 1.8; font-family: serif; }
+h1 { font-size: 1.4em; text-align: center; margin: 1.5em 0 1em; }
+h1.volume { font-size: 1.8em; margin-top: 30%; }
+h2 { font-size: 1.2em; margin: 1.5em 0 0.5em; }
+p { margin: 0.8em 0; }
+.cover { margin: 0; padding: 0; text-align: center; }
+.cover img { max-width: 100%; max-height: 100%; }
+.title-page { margin-top: 30%; text-align: center; }
+.title-page p { text-indent: 0; }
+.title-page .book-title { font-size: 2em; font-weight: bold; }
+.title-page .author { margin-top: 2em; }
+.about p.meta { margin: 0.3em 0; text-indent: 0; }
+"#;
+
+const COMPACT_CSS: &str = r#"body { margin: 0 2%; line-height: 1.5; }
+h1 { margin: 1em 0 0.5em; }
+p { margin: 0.3em 0; }
+"#;
+
+const INDENT_CSS: &str = r#"p { margin: 0; text-indent: 2em; }
+"#;
+
+const VERTICAL_CSS: &str = r#"html { writing-mode: vertical-rl; -epub-writing-mode: vertical-rl; -webkit-writing-mode: vertical-rl; }
+body { margin: 5% 0; }
+h1 { margin: 0 1em 0 1.5em; }
+h1.volume { margin-top: 0; margin-right: 30%; }
+p { margin: 0; text-indent: 2em; }
+.title-page { margin-top: 0; margin-right: 30%; }
+"#;
+
+/// EPUB 内置样式主题
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EpubTheme {
+    /// 段间留白，不缩进
+    #[default]
+    Default,
+    /// 较小的行距和边距
+    Compact,
+    /// 中文排版习惯的首行缩进两字
+    Indent,
+    /// 竖排，从右向左翻页
+    Vertical,
+}
+
+impl EpubTheme {
+    pub const NAMES: [&'static str; 4] = ["default", "compact", "indent", "vertical"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "default" => Some(Self::Default),
+            "compact" => Some(Self::Compact),
+            "indent" => Some(Self::Indent),
+            "vertical" => Some(Self::Vertical),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Compact => "compact",
+            Self::Indent => "indent",
+            Self::Vertical => "vertical",
+        }
+    }
+
+    /// 基础样式加上主题样式
+    pub fn css(&self) -> String {
+        let theme = match self {
+            Self::Default => "",
+            Self::Compact => COMPACT_CSS,
+            Self::Indent => INDENT_CSS,
+            Self::Vertical => VERTICAL_CSS,
+        };
+        format!("{}{}", BASE_CSS, theme)
+    }
+}
+
+/// EPUB 导出选项
+#[derive(Debug, Clone, Default)]
+pub struct EpubOptions {
+    /// 封面图片，为 `None` 时不设置封面
+    pub cover: Option<CoverImage>,
+    pub theme: EpubTheme,
+    /// 追加在主题样式之后的自定义 CSS
+    pub custom_css: Option<String>,
+}
+
+impl EpubOptions {
+    fn stylesheet(&self) -> String {
+        match &self.custom_css {
+            Some(css) => format!("{}\n/* 自定义样式 */\n{}\n", self.theme.css(), css.trim_end()),
+            None => self.theme.css(),
+        }
+    }
+}
+
+/// EPUB 的唯一标识，同一本书多次导出保持不变
+pub fn epub_identifier(book_id: &str) -> String {
+    format!("fanqie:{}", book_id)
+}
+
+pub fn export_epub(
+    book_info: &BookInfo,
+    chapters: &[ChapterContent],
+    output_path: &Path,
+    options: &EpubOptions,
+) -> Result<()> {
     let mut builder = epub_builder::EpubBuilder::new(epub_builder::ZipLibrary::new()
         .map_err(|e| FanqieError::EpubGeneration(format!("创建 ZIP 库失败: {}", e)))?)
         .map_err(|e| FanqieError::EpubGeneration(format!("创建 EPUB 构建器失败: {}", e)))?;
@@ -44,47 +298,282 @@ pub fn export_epub(book_info: &BookInfo, chapters: &[ChapterContent], save_path:
     builder.set_title(&book_info.book_name);
     builder.set_authors(vec![book_info.author.clone()]);
     builder.set_lang("zh-CN");
+    builder.set_generator(format!("fqdl {}", crate::VERSION));
+
+    let description = book_info.get_abstract().trim();
+    if !description.is_empty() {
+        builder.add_description(description);
+    }
+
+    builder.stylesheet(options.stylesheet().as_bytes())
+        .map_err(|e| FanqieError::EpubGeneration(format!("添加样式失败: {}", e)))?;
+
+    if let Some(cover) = &options.cover {
+        let image_name = format!("cover.{}", cover.extension());
+        builder.add_cover_image(&image_name, cover.data.as_slice(), cover.mime_type.as_str())
+            .map_err(|e| FanqieError::EpubGeneration(format!("添加封面失败: {}", e)))?;
+        builder.add_content(
+            epub_builder::EpubContent::new("cover.xhtml", cover_page(&book_info.book_name, &image_name).as_bytes())
+                .reftype(epub_builder::ReferenceType::Cover)
+        ).map_err(|e| FanqieError::EpubGeneration(format!("添加封面失败: {}", e)))?;
+    }
+
+    builder.add_content(
+        epub_builder::EpubContent::new("title.xhtml", title_page(book_info).as_bytes())
+            .reftype(epub_builder::ReferenceType::TitlePage)
+    ).map_err(|e| FanqieError::EpubGeneration(format!("添加扉页失败: {}", e)))?;
+    builder.add_content(
+        epub_builder::EpubContent::new("about.xhtml", about_page(book_info, chapters.len()).as_bytes())
+            .title(ABOUT_TITLE)
+            .reftype(epub_builder::ReferenceType::Preface)
+    ).map_err(|e| FanqieError::EpubGeneration(format!("添加书籍信息页失败: {}", e)))?;
 
+    // 有分卷的章节挂在分卷页下，目录为 分卷 → 章节 两级
+    let mut volumes = VolumeTracker::new(None);
+    let mut volume_count = 0;
     for (index, chapter) in chapters.iter().enumerate() {
+        if let Some(volume) = volumes.enter(chapter) {
+            volume_count += 1;
+            builder.add_content(
+                epub_builder::EpubContent::new(format!("volume_{:04}.xhtml", volume_count), volume_to_html(volume).as_bytes())
+                    .title(volume)
+                    .level(1)
+            ).map_err(|e| FanqieError::EpubGeneration(format!("添加分卷失败: {}", e)))?;
+        }
+
         let chapter_filename = format!("chapter_{:04}.xhtml", index);
         let html_content = chapter_to_html(&chapter.title, &chapter.content);
+        let level = if chapter.volume_name.is_some() { 2 } else { 1 };
         
         builder.add_content(
             epub_builder::EpubContent::new(&chapter_filename, html_content.as_bytes())
                 .title(&chapter.title)
+                .level(level)
         ).map_err(|e| FanqieError::EpubGeneration(format!("添加章节失败: {}", e)))?;
     }
 
-    builder.generate(file)
+    let mut epub = Vec::new();
+    builder.generate(&mut epub)
         .map_err(|e| FanqieError::EpubGeneration(format!("生成 EPUB 失败: {}", e)))?;
 
-    Ok(output_path)
+    let epub = add_book_metadata(epub, book_info, options, Utc::now())?;
+    fs::write(output_path, epub)
+        .map_err(|e| FanqieError::FileWrite(format!("写入 EPUB 失败: {}", e)))
 }
 
-fn chapter_to_html(title: &str, content: &str) -> String {
-    let escaped_title = encode_text(title);
-    let escaped_content = encode_text(content);
-    
-    let paragraphs: Vec<&str> = escaped_content.split('\n').collect();
-    let formatted_content = paragraphs
-        .iter()
-        .map(|p| format!("<p>{}</p>", p.trim()))
+/// epub-builder 只支持随机 UUID 和少数元数据，生成后在 OPF 中改用 `fanqie:<book_id>` 作为唯一标识，
+/// 并补充连载状态、字数和下载日期；竖排主题还需声明从右向左翻页
+fn add_book_metadata(
+    epub: Vec<u8>,
+    book_info: &BookInfo,
+    options: &EpubOptions,
+    downloaded_at: DateTime<Utc>,
+) -> Result<Vec<u8>> {
+    const OPF_PATH: &str = "OEBPS/content.opf";
+    let zip_error = |e: zip::result::ZipError| FanqieError::EpubGeneration(format!("写入元数据失败: {}", e));
+
+    let mut archive = zip::ZipArchive::new(Cursor::new(epub)).map_err(zip_error)?;
+    let mut opf = String::new();
+    archive.by_name(OPF_PATH).map_err(zip_error)?.read_to_string(&mut opf)?;
+
+    if let Some(uuid) = between(&opf, "<dc:identifier id=\"epub-id-1\">", "</dc:identifier>") {
+        let element = |id: &str| format!("<dc:identifier id=\"epub-id-1\">{}</dc:identifier>", id);
+        opf = opf.replacen(&element(uuid), &element(&encode_text(&epub_identifier(&book_info.book_id))), 1);
+    }
+
+    let mut metadata = vec![
+        format!("<dc:date opf:event=\"download\">{}</dc:date>", downloaded_at.format("%Y-%m-%d")),
+        format!("<meta name=\"fanqie:downloaded_at\" content=\"{}\"/>", downloaded_at.format("%Y-%m-%dT%H:%M:%SZ")),
+    ];
+    if !book_info.get_status().is_empty() {
+        metadata.push(format!(
+            "<meta name=\"fanqie:status\" content=\"{}\"/>",
+            encode_double_quoted_attribute(book_info.get_status())
+        ));
+    }
+    if let Some(word_count) = book_info.word_count {
+        metadata.push(format!("<meta name=\"fanqie:word_count\" content=\"{}\"/>", word_count));
+    }
+    if options.theme == EpubTheme::Vertical {
+        metadata.push("<meta name=\"primary-writing-mode\" content=\"vertical-rl\"/>".to_string());
+        opf = opf.replacen("<spine toc=\"ncx\">", "<spine toc=\"ncx\" page-progression-direction=\"rtl\">", 1);
+    }
+    let metadata: String = metadata.iter().map(|line| format!("    {}\n", line)).collect();
+    let opf = opf.replacen("  </metadata>", &format!("{}  </metadata>", metadata), 1);
+
+    // 其余文件原样复制，保证 mimetype 仍是第一个且不压缩
+    let mut output = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for index in 0..archive.len() {
+        let file = archive.by_index_raw(index).map_err(zip_error)?;
+        if file.name() == OPF_PATH {
+            drop(file);
+            let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+            output.start_file(OPF_PATH, options).map_err(zip_error)?;
+            output.write_all(opf.as_bytes())?;
+        } else {
+            output.raw_copy_file(file).map_err(zip_error)?;
+        }
+    }
+
+    Ok(output.finish().map_err(zip_error)?.into_inner())
+}
+
+/// 从本程序导出的 EPUB 中按顺序读回章节标题和正文 (章节ID和分卷为空)
+pub fn read_epub_chapters(path: &Path) -> Result<Vec<ChapterContent>> {
+    let file = File::open(path)
+        .map_err(|e| FanqieError::EpubGeneration(format!("打开 EPUB 失败: {}", e)))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| FanqieError::EpubGeneration(format!("读取 EPUB 失败: {}", e)))?;
+
+    // 按文件名中的章节序号排序，超过 9999 章时序号位数不同，不能按字符串排序
+    let mut names: Vec<(usize, String)> = archive
+        .file_names()
+        .filter_map(|name| {
+            let index = name.strip_prefix("OEBPS/chapter_")?.strip_suffix(".xhtml")?.parse().ok()?;
+            Some((index, name.to_string()))
+        })
+        .collect();
+    names.sort();
+
+    let mut chapters = Vec::with_capacity(names.len());
+    for (_, name) in names {
+        let mut html = String::new();
+        archive
+            .by_name(&name)
+            .map_err(|e| FanqieError::EpubGeneration(format!("读取 {} 失败: {}", name, e)))?
+            .read_to_string(&mut html)
+            .map_err(|e| FanqieError::EpubGeneration(format!("读取 {} 失败: {}", name, e)))?;
+        chapters.push(html_to_chapter(&html));
+    }
+
+    Ok(chapters)
+}
+
+/// `chapter_to_html` 的逆过程
+fn html_to_chapter(html: &str) -> ChapterContent {
+    let title = between(html, "<h1>", "</h1>").unwrap_or("");
+    let body = html.split("</h1>").nth(1).unwrap_or("");
+
+    let content = body
+        .lines()
+        .filter_map(|line| between(line, "<p>", "</p>"))
+        .map(|p| decode_html_entities(p).to_string())
         .collect::<Vec<String>>()
         .join("\n");
 
+    ChapterContent {
+        chapter_id: String::new(),
+        title: decode_html_entities(title).to_string(),
+        content,
+        volume_name: None,
+    }
+}
+
+fn between<'a>(s: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let from = s.find(start)? + start.len();
+    let to = s[from..].find(end)? + from;
+    Some(&s[from..to])
+}
+
+/// 生成引用 `stylesheet.css` 的 XHTML 页面，`title` 和 `body` 需已转义
+fn xhtml_page(title: &str, body: &str) -> String {
     format!(
         r#"<?xml version="1.0" encoding="utf-8"?>
 <!DOCTYPE html>
 <html xmlns="http://www.w3.org/1999/xhtml">
 <head>
     <title>{}</title>
+    <link rel="stylesheet" type="text/css" href="stylesheet.css"/>
 </head>
 <body>
-    <h1>{}</h1>
-    {}
+{}
 </body>
 </html>"#,
-        escaped_title, escaped_title, formatted_content
+        title, body
+    )
+}
+
+fn chapter_to_html(title: &str, content: &str) -> String {
+    let escaped_title = encode_text(title);
+    let escaped_content = encode_text(content);
+    
+    let paragraphs: Vec<&str> = escaped_content.split('\n').collect();
+    let formatted_content = paragraphs
+        .iter()
+        .map(|p| format!("<p>{}</p>", p.trim()))
+        .collect::<Vec<String>>()
+        .join("\n");
+
+    xhtml_page(&escaped_title, &format!("    <h1>{}</h1>\n    {}", escaped_title, formatted_content))
+}
+
+fn volume_to_html(volume: &str) -> String {
+    let escaped_volume = encode_text(volume);
+    xhtml_page(&escaped_volume, &format!("    <h1 class=\"volume\">{}</h1>", escaped_volume))
+}
+
+fn cover_page(title: &str, image: &str) -> String {
+    xhtml_page(
+        &encode_text(title),
+        &format!(
+            "    <div class=\"cover\"><img src=\"{}\" alt=\"{}\"/></div>",
+            encode_double_quoted_attribute(image),
+            encode_double_quoted_attribute(title)
+        ),
+    )
+}
+
+/// 扉页: 书名、作者和连载状态
+fn title_page(book_info: &BookInfo) -> String {
+    let mut lines = vec![
+        format!("<p class=\"book-title\">{}</p>", encode_text(&book_info.book_name)),
+        format!("<p class=\"author\">{}</p>", encode_text(&book_info.author)),
+    ];
+    if !book_info.get_status().is_empty() {
+        lines.push(format!("<p class=\"status\">{}</p>", book_info.get_status()));
+    }
+
+    xhtml_page(
+        &encode_text(&book_info.book_name),
+        &format!("    <div class=\"title-page\">\n    {}\n    </div>", lines.join("\n    ")),
+    )
+}
+
+/// “关于本书”页: 书籍信息与简介
+fn about_page(book_info: &BookInfo, chapter_count: usize) -> String {
+    let mut meta = vec![
+        ("书名", book_info.book_name.clone()),
+        ("作者", book_info.author.clone()),
+    ];
+    if !book_info.get_status().is_empty() {
+        meta.push(("状态", book_info.get_status().to_string()));
+    }
+    if let Some(word_count) = book_info.word_count {
+        meta.push(("字数", word_count.to_string()));
+    }
+    meta.push(("章节", format!("{} 章", chapter_count)));
+    meta.push(("书籍ID", book_info.book_id.clone()));
+
+    let mut lines: Vec<String> = meta
+        .iter()
+        .map(|(label, value)| format!("<p class=\"meta\">{}: {}</p>", label, encode_text(value)))
+        .collect();
+
+    let description = book_info.get_abstract().trim();
+    if !description.is_empty() {
+        lines.push("<h2>简介</h2>".to_string());
+        lines.extend(
+            description
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(|line| format!("<p>{}</p>", encode_text(line))),
+        );
+    }
+
+    xhtml_page(
+        ABOUT_TITLE,
+        &format!("    <h1>{}</h1>\n    <div class=\"about\">\n    {}\n    </div>", ABOUT_TITLE, lines.join("\n    ")),
     )
 }
 
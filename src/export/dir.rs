@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::Path;
+
+use crate::api::{BookInfo, ChapterContent};
+use crate::error::{FanqieError, Result};
+
+use super::markdown::{book_header, chapter_markdown, escape_line};
+use super::{sanitize_filename, VolumeTracker};
+
+/// 按章节分文件时每章的文件名: `0001-章节标题.<extension>`
+pub fn chapter_file_name(number: usize, title: &str, extension: &str) -> String {
+    format!("{:04}-{}.{}", number, sanitize_filename(title), extension)
+}
+
+/// 按章节分文件导出到 `dir`，并写入列出全部章节的 `index.<format>`。
+/// 前 `existing` 章的文件已存在 (连载更新)，只写入之后的章节，这些章节的正文可以为空
+pub fn export_dir(
+    book_info: &BookInfo,
+    chapters: &[ChapterContent],
+    dir: &Path,
+    format: &str,
+    existing: usize,
+) -> Result<()> {
+    fs::create_dir_all(dir)
+        .map_err(|e| FanqieError::FileWrite(format!("创建目录失败: {}", e)))?;
+
+    for (index, chapter) in chapters.iter().enumerate().skip(existing) {
+        let file_name = chapter_file_name(index + 1, &chapter.title, format);
+        let text = match format {
+            "md" => chapter_markdown(chapter),
+            _ => format!("{}\n\n{}\n", chapter.title, chapter.content),
+        };
+        fs::write(dir.join(&file_name), text)
+            .map_err(|e| FanqieError::FileWrite(format!("写入 {} 失败: {}", file_name, e)))?;
+    }
+
+    let index = match format {
+        "md" => markdown_index(book_info, chapters),
+        _ => txt_index(book_info, chapters),
+    };
+    fs::write(dir.join(format!("index.{}", format)), index)
+        .map_err(|e| FanqieError::FileWrite(format!("写入索引失败: {}", e)))
+}
+
+fn markdown_index(book_info: &BookInfo, chapters: &[ChapterContent]) -> String {
+    let mut index = book_header(book_info);
+    index.push_str("\n## 目录\n");
+
+    let mut volumes = VolumeTracker::new(None);
+    for (number, chapter) in (1..).zip(chapters) {
+        if let Some(volume) = volumes.enter(chapter) {
+            index.push_str(&format!("\n### {}\n\n", escape_line(volume)));
+        } else if number == 1 {
+            index.push('\n');
+        }
+        // 文件名可能含空格，用尖括号包裹链接地址
+        index.push_str(&format!(
+            "- [{}](<{}>)\n",
+            escape_line(&chapter.title),
+            chapter_file_name(number, &chapter.title, "md")
+        ));
+    }
+
+    index
+}
+
+fn txt_index(book_info: &BookInfo, chapters: &[ChapterContent]) -> String {
+    let mut index = format!("书名: {}\n作者: {}\n", book_info.book_name, book_info.author);
+    if !book_info.get_status().is_empty() {
+        index.push_str(&format!("状态: {}\n", book_info.get_status()));
+    }
+    index.push_str(&format!("共 {} 章\n\n{}\n", chapters.len(), "=".repeat(50)));
+
+    let mut volumes = VolumeTracker::new(None);
+    for (number, chapter) in (1..).zip(chapters) {
+        if let Some(volume) = volumes.enter(chapter) {
+            index.push_str(&format!("\n{}\n", volume));
+        } else if number == 1 {
+            index.push('\n');
+        }
+        index.push_str(&format!("{}\n", chapter_file_name(number, &chapter.title, "txt")));
+    }
+
+    index
+}
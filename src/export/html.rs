@@ -0,0 +1,224 @@
+use std::fs;
+use std::path::Path;
+use html_escape::{decode_html_entities, encode_double_quoted_attribute, encode_text};
+
+use crate::api::{BookInfo, ChapterContent};
+use crate::error::{FanqieError, Result};
+
+use super::{between, VolumeTracker};
+
+const HTML_CSS: &str = r#":root { --bg: #fbfaf7; --fg: #2b2b2b; --muted: #8a8580; --panel: #f1eee8; --accent: #b5542c; --font-size: 18px; }
+:root[data-theme="dark"] { --bg: #1d1e20; --fg: #d6d3cd; --muted: #8d8a85; --panel: #26272a; --accent: #e08a5f; }
+* { box-sizing: border-box; }
+html { scroll-behavior: smooth; }
+body { margin: 0; background: var(--bg); color: var(--fg); font-family: "Songti SC", "Noto Serif CJK SC", serif; }
+#toc { position: fixed; top: 0; bottom: 0; left: 0; width: 280px; overflow-y: auto; padding: 1em; background: var(--panel); font-size: 15px; }
+#toc .book-title { margin: 0 0 0.2em; font-size: 1.3em; }
+#toc .author { margin: 0 0 1em; color: var(--muted); }
+#toc ol { margin: 0; padding-left: 1.2em; }
+#toc li { margin: 0.3em 0; }
+#toc li.volume { list-style: none; margin-left: -1.2em; font-weight: bold; }
+#toc li.volume li { font-weight: normal; }
+#toc a { color: var(--fg); text-decoration: none; }
+#toc a:hover { color: var(--accent); }
+.controls { display: flex; gap: 0.4em; margin-bottom: 1em; }
+.controls button { flex: 1; padding: 0.3em; border: 1px solid var(--muted); border-radius: 4px; background: var(--bg); color: var(--fg); cursor: pointer; }
+main { margin-left: 280px; padding: 2em 8%; max-width: calc(280px + 52em); font-size: var(--font-size); line-height: 1.9; }
+.book-header { margin-bottom: 3em; }
+.book-header h1 { font-size: 2em; margin-bottom: 0.2em; }
+.book-header .meta { color: var(--muted); text-indent: 0; }
+.volume-title { margin: 3em 0 1em; text-align: center; font-size: 1.6em; color: var(--accent); }
+.chapter h2 { margin: 2.5em 0 1em; text-align: center; font-size: 1.3em; }
+.chapter p { margin: 0.4em 0; text-indent: 2em; }
+#toc-toggle { display: none; }
+@media (max-width: 800px) {
+  #toc { transform: translateX(-100%); transition: transform 0.2s; z-index: 10; width: 80%; }
+  #toc.open { transform: none; }
+  main { margin-left: 0; padding: 1.5em 5%; }
+  #toc-toggle { display: block; position: fixed; right: 1em; bottom: 1em; z-index: 20; padding: 0.5em 0.8em; border: none; border-radius: 4px; background: var(--accent); color: #fff; }
+}
+"#;
+
+/// 深浅色和字号设置保存在 localStorage 中，刷新后保持
+const HTML_SCRIPT: &str = r#"(function () {
+  var root = document.documentElement;
+  var store = window.localStorage;
+  var theme = store.getItem("fqdl-theme");
+  var size = parseInt(store.getItem("fqdl-font-size"), 10) || 18;
+  if (theme) root.setAttribute("data-theme", theme);
+  root.style.setProperty("--font-size", size + "px");
+
+  document.getElementById("theme-toggle").onclick = function () {
+    theme = root.getAttribute("data-theme") === "dark" ? "light" : "dark";
+    root.setAttribute("data-theme", theme);
+    store.setItem("fqdl-theme", theme);
+  };
+  function resize(delta) {
+    size = Math.min(32, Math.max(12, size + delta));
+    root.style.setProperty("--font-size", size + "px");
+    store.setItem("fqdl-font-size", size);
+  }
+  document.getElementById("font-smaller").onclick = function () { resize(-2); };
+  document.getElementById("font-larger").onclick = function () { resize(2); };
+
+  var toc = document.getElementById("toc");
+  document.getElementById("toc-toggle").onclick = function () { toc.classList.toggle("open"); };
+  toc.addEventListener("click", function (event) {
+    if (event.target.tagName === "A") toc.classList.remove("open");
+  });
+})();
+"#;
+
+/// 导出为单个 HTML 文件，样式和脚本内嵌，左侧为可点击的目录
+pub fn export_html(book_info: &BookInfo, chapters: &[ChapterContent], output_path: &Path) -> Result<()> {
+    let title = encode_text(&book_info.book_name);
+    let author = encode_text(&book_info.author);
+
+    let mut toc = Vec::new();
+    let mut body = Vec::new();
+    let mut volumes = VolumeTracker::new(None);
+    let mut volume_count = 0;
+    let mut in_volume = false;
+
+    for (index, chapter) in chapters.iter().enumerate() {
+        let anchor = chapter_anchor(index);
+        let chapter_title = encode_text(&chapter.title);
+
+        if let Some(volume) = volumes.enter(chapter) {
+            volume_count += 1;
+            if in_volume {
+                toc.push("</ol></li>".to_string());
+            }
+            let volume_anchor = format!("volume-{}", volume_count);
+            toc.push(format!(
+                "<li class=\"volume\"><a href=\"#{}\">{}</a><ol>",
+                volume_anchor,
+                encode_text(volume)
+            ));
+            body.push(format!("<h2 class=\"volume-title\" id=\"{}\">{}</h2>", volume_anchor, encode_text(volume)));
+            in_volume = true;
+        } else if in_volume && chapter.volume_name.is_none() {
+            toc.push("</ol></li>".to_string());
+            in_volume = false;
+        }
+
+        toc.push(format!("<li><a href=\"#{}\">{}</a></li>", anchor, chapter_title));
+
+        let paragraphs: Vec<String> = chapter.content
+            .split('\n')
+            .map(|p| format!("<p>{}</p>", encode_text(p.trim())))
+            .collect();
+        body.push(format!(
+            "<article class=\"chapter\" id=\"{}\">\n<h2>{}</h2>\n{}\n</article>",
+            anchor,
+            chapter_title,
+            paragraphs.join("\n")
+        ));
+    }
+    if in_volume {
+        toc.push("</ol></li>".to_string());
+    }
+
+    let mut meta = vec![format!("作者: {}", author)];
+    if !book_info.get_status().is_empty() {
+        meta.push(format!("状态: {}", book_info.get_status()));
+    }
+    if let Some(word_count) = book_info.word_count {
+        meta.push(format!("字数: {}", word_count));
+    }
+    meta.push(format!("共 {} 章", chapters.len()));
+
+    let description: String = book_info.get_abstract()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("<p>{}</p>\n", encode_text(line)))
+        .collect();
+
+    let html = format!(
+        r##"<!DOCTYPE html>
+<html lang="zh-CN">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<meta name="generator" content="fqdl {version}">
+<meta name="fanqie:book_id" content="{book_id}">
+<title>{title}</title>
+<style>
+{css}</style>
+</head>
+<body>
+<nav id="toc">
+<h1 class="book-title"><a href="#top">{title}</a></h1>
+<p class="author">{author}</p>
+<div class="controls">
+<button id="theme-toggle" type="button" title="切换深色/浅色">深/浅</button>
+<button id="font-smaller" type="button" title="减小字号">A-</button>
+<button id="font-larger" type="button" title="增大字号">A+</button>
+</div>
+<ol>
+{toc}
+</ol>
+</nav>
+<button id="toc-toggle" type="button">目录</button>
+<main>
+<header class="book-header" id="top">
+<h1>{title}</h1>
+<p class="meta">{meta}</p>
+{description}</header>
+{body}
+</main>
+<script>
+{script}</script>
+</body>
+</html>
+"##,
+        version = crate::VERSION,
+        book_id = encode_double_quoted_attribute(&book_info.book_id),
+        title = title,
+        author = author,
+        css = HTML_CSS,
+        toc = toc.join("\n"),
+        meta = meta.join(" · "),
+        description = description,
+        body = body.join("\n"),
+        script = HTML_SCRIPT,
+    );
+
+    fs::write(output_path, html)
+        .map_err(|e| FanqieError::FileWrite(format!("写入 HTML 失败: {}", e)))
+}
+
+/// 从本程序导出的 HTML 中按顺序读回章节标题和正文 (章节ID和分卷为空)
+pub fn read_html_chapters(path: &Path) -> Result<Vec<ChapterContent>> {
+    let html = fs::read_to_string(path)
+        .map_err(|e| FanqieError::FileWrite(format!("读取 HTML 失败: {}", e)))?;
+
+    let chapters = html
+        .split("<article class=\"chapter\"")
+        .skip(1)
+        .map(|section| {
+            let section = section.split("</article>").next().unwrap_or("");
+            let title = between(section, "<h2>", "</h2>").unwrap_or("");
+            let content = section
+                .lines()
+                .filter_map(|line| between(line, "<p>", "</p>"))
+                .map(|p| decode_html_entities(p).to_string())
+                .collect::<Vec<String>>()
+                .join("\n");
+
+            ChapterContent {
+                chapter_id: String::new(),
+                title: decode_html_entities(title).to_string(),
+                content,
+                volume_name: None,
+            }
+        })
+        .collect();
+
+    Ok(chapters)
+}
+
+fn chapter_anchor(index: usize) -> String {
+    format!("chapter-{}", index + 1)
+}
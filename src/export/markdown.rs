@@ -0,0 +1,108 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use crate::api::{BookInfo, ChapterContent};
+use crate::error::{FanqieError, Result};
+
+use super::VolumeTracker;
+
+/// 导出为单个 Markdown 文件: `# 书名`、`## 章节标题`，有分卷时卷名为 `## 卷名`、其中的章节为 `### 章节标题`
+pub fn export_markdown(book_info: &BookInfo, chapters: &[ChapterContent], output_path: &Path) -> Result<()> {
+    let mut file = File::create(output_path)
+        .map_err(|e| FanqieError::FileWrite(format!("创建文件失败: {}", e)))?;
+
+    write!(file, "{}", book_header(book_info))
+        .map_err(|e| FanqieError::FileWrite(format!("写入失败: {}", e)))?;
+
+    write_markdown_chapters(&mut file, chapters, None)
+}
+
+/// 将章节追加到已导出的 Markdown 文件末尾，`last_volume` 为文件中最后一章所属的分卷
+pub fn append_markdown(path: &Path, chapters: &[ChapterContent], last_volume: Option<&str>) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .append(true)
+        .open(path)
+        .map_err(|e| FanqieError::FileWrite(format!("打开文件失败: {}", e)))?;
+
+    write_markdown_chapters(&mut file, chapters, last_volume)
+}
+
+pub(super) fn write_markdown_chapters<W: Write>(file: &mut W, chapters: &[ChapterContent], last_volume: Option<&str>) -> Result<()> {
+    let mut volumes = VolumeTracker::new(last_volume);
+    for chapter in chapters {
+        if let Some(volume) = volumes.enter(chapter) {
+            writeln!(file, "\n## {}", escape_line(volume))
+                .map_err(|e| FanqieError::FileWrite(format!("写入失败: {}", e)))?;
+        }
+        // 分卷中的章节比卷名低一级
+        let level = if chapter.volume_name.is_some() { "###" } else { "##" };
+        writeln!(file, "\n{} {}\n\n{}", level, escape_line(&chapter.title), paragraphs(&chapter.content))
+            .map_err(|e| FanqieError::FileWrite(format!("写入失败: {}", e)))?;
+    }
+
+    Ok(())
+}
+
+/// 书名、作者等信息和简介 (引用块)
+pub(super) fn book_header(book_info: &BookInfo) -> String {
+    let mut header = format!("# {}\n\n作者: {}  \n", escape_line(&book_info.book_name), escape_line(&book_info.author));
+    if !book_info.get_status().is_empty() {
+        header.push_str(&format!("状态: {}  \n", book_info.get_status()));
+    }
+    if let Some(word_count) = book_info.word_count {
+        header.push_str(&format!("字数: {}  \n", word_count));
+    }
+
+    let description: Vec<String> = book_info.get_abstract()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("> {}", escape_line(line)))
+        .collect();
+    if !description.is_empty() {
+        header.push_str(&format!("\n{}\n", description.join("\n>\n")));
+    }
+
+    header
+}
+
+/// 单章 Markdown: `# 章节标题` 加正文
+pub(super) fn chapter_markdown(chapter: &ChapterContent) -> String {
+    format!("# {}\n\n{}\n", escape_line(&chapter.title), paragraphs(&chapter.content))
+}
+
+/// 每行正文为一个段落，段落之间空一行
+fn paragraphs(content: &str) -> String {
+    content
+        .split('\n')
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(escape_line)
+        .collect::<Vec<String>>()
+        .join("\n\n")
+}
+
+/// 转义会被解析为 Markdown 语法的字符，使正文按原样显示
+pub(super) fn escape_line(line: &str) -> String {
+    let mut escaped = String::with_capacity(line.len());
+    for c in line.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    // 行首的列表和分隔线标记
+    match escaped.chars().next() {
+        Some('-' | '+' | '=' | '~') => format!("\\{}", escaped),
+        Some(c) if c.is_ascii_digit() => {
+            let digits = escaped.chars().take_while(|c| c.is_ascii_digit()).count();
+            match escaped[digits..].chars().next() {
+                Some(marker @ ('.' | ')')) => format!("{}\\{}{}", &escaped[..digits], marker, &escaped[digits + 1..]),
+                _ => escaped,
+            }
+        }
+        _ => escaped,
+    }
+}
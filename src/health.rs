@@ -0,0 +1,218 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::config::{state_dir, ConfigParams};
+use crate::error::{FanqieError, Result};
+
+/// 延迟的指数移动平均中新样本的权重
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// 正常使用
+    Closed,
+    /// 连续失败次数过多，冷却结束前跳过该节点
+    Open,
+    /// 冷却已结束，允许再次尝试，成功后恢复为 Closed
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CircuitState::Closed => "正常",
+            CircuitState::Open => "熔断",
+            CircuitState::HalfOpen => "半开",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeStats {
+    pub successes: u64,
+    pub failures: u64,
+    pub consecutive_failures: u32,
+    pub avg_latency_ms: Option<f64>,
+    pub last_error: Option<String>,
+    pub open_until: Option<DateTime<Utc>>,
+    /// 节点要求暂停请求 (HTTP 429 或限流错误码) 的截止时间
+    #[serde(default)]
+    pub throttled_until: Option<DateTime<Utc>>,
+}
+
+impl NodeStats {
+    pub fn state(&self, now: DateTime<Utc>) -> CircuitState {
+        match self.open_until {
+            Some(until) if now < until => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+            None => CircuitState::Closed,
+        }
+    }
+
+    pub fn is_throttled(&self, now: DateTime<Utc>) -> bool {
+        self.throttled_until.is_some_and(|until| now < until)
+    }
+
+    pub fn success_rate(&self) -> Option<f64> {
+        let total = self.successes + self.failures;
+        (total > 0).then(|| self.successes as f64 / total as f64)
+    }
+}
+
+/// 各节点的成功/失败次数、延迟和熔断状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeHealth {
+    pub nodes: HashMap<String, NodeStats>,
+    #[serde(skip, default = "default_failure_threshold")]
+    failure_threshold: u32,
+    #[serde(skip, default = "default_cooldown")]
+    cooldown: Duration,
+}
+
+fn default_failure_threshold() -> u32 { 5 }
+fn default_cooldown() -> Duration { Duration::from_secs(60) }
+
+impl Default for NodeHealth {
+    fn default() -> Self {
+        Self {
+            nodes: HashMap::new(),
+            failure_threshold: default_failure_threshold(),
+            cooldown: default_cooldown(),
+        }
+    }
+}
+
+impl NodeHealth {
+    pub fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            nodes: HashMap::new(),
+            failure_threshold: failure_threshold.max(1),
+            cooldown,
+        }
+    }
+
+    pub fn from_params(params: &ConfigParams) -> Self {
+        Self::new(params.circuit_failure_threshold, Duration::from_secs(params.circuit_cooldown))
+    }
+
+    /// 保留当前的熔断参数，替换统计数据
+    pub fn restore(&mut self, other: NodeHealth) {
+        self.nodes = other.nodes;
+    }
+
+    pub fn get(&self, base_url: &str) -> Option<&NodeStats> {
+        self.nodes.get(base_url)
+    }
+
+    pub fn state(&self, base_url: &str) -> CircuitState {
+        self.nodes
+            .get(base_url)
+            .map(|s| s.state(Utc::now()))
+            .unwrap_or(CircuitState::Closed)
+    }
+
+    pub fn record_success(&mut self, base_url: &str, latency: Duration) {
+        let stats = self.nodes.entry(base_url.to_string()).or_default();
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+
+        stats.successes += 1;
+        stats.consecutive_failures = 0;
+        stats.open_until = None;
+        stats.throttled_until = None;
+        stats.avg_latency_ms = Some(match stats.avg_latency_ms {
+            Some(avg) => avg + (latency_ms - avg) * LATENCY_SMOOTHING,
+            None => latency_ms,
+        });
+    }
+
+    pub fn record_failure(&mut self, base_url: &str, error: &str) {
+        let stats = self.nodes.entry(base_url.to_string()).or_default();
+
+        stats.failures += 1;
+        stats.consecutive_failures += 1;
+        stats.last_error = Some(error.to_string());
+
+        if stats.consecutive_failures >= self.failure_threshold {
+            let cooldown = chrono::Duration::from_std(self.cooldown).unwrap_or(chrono::Duration::zero());
+            stats.open_until = Some(Utc::now() + cooldown);
+        }
+    }
+
+    /// 节点要求暂停 `pause` 后再请求，限流不计入连续失败次数，不会触发熔断
+    pub fn record_throttled(&mut self, base_url: &str, error: &str, pause: Duration) {
+        let stats = self.nodes.entry(base_url.to_string()).or_default();
+        let until = Utc::now() + chrono::Duration::from_std(pause).unwrap_or(chrono::Duration::zero());
+
+        stats.failures += 1;
+        stats.last_error = Some(error.to_string());
+        stats.throttled_until = Some(stats.throttled_until.map_or(until, |current| current.max(until)));
+    }
+
+    /// 节点限流暂停的剩余时间
+    pub fn throttle_remaining(&self, base_url: &str) -> Option<Duration> {
+        let until = self.nodes.get(base_url)?.throttled_until?;
+        (until - Utc::now()).to_std().ok().filter(|d| !d.is_zero())
+    }
+
+    /// 按健康程度排序节点: 未熔断的节点在前，依次按连续失败次数、平均延迟排序，
+    /// 没有记录的节点保持原有顺序排在有延迟记录的节点之后；
+    /// 熔断中的节点被跳过，除非所有节点都处于熔断状态；限流暂停中的节点排在最后
+    pub fn order<T>(&self, nodes: Vec<(T, String)>) -> Vec<(T, String)> {
+        let now = Utc::now();
+        let (mut open, available): (Vec<_>, Vec<_>) = nodes
+            .into_iter()
+            .partition(|(_, url)| {
+                self.nodes.get(url).map(|s| s.state(now)) == Some(CircuitState::Open)
+            });
+        let (mut throttled, mut available): (Vec<_>, Vec<_>) = available
+            .into_iter()
+            .partition(|(_, url)| self.nodes.get(url).is_some_and(|s| s.is_throttled(now)));
+
+        available.sort_by(|(_, a), (_, b)| {
+            let a = self.nodes.get(a);
+            let b = self.nodes.get(b);
+            let failures = |s: Option<&NodeStats>| s.map(|s| s.consecutive_failures).unwrap_or(0);
+            let latency = |s: Option<&NodeStats>| s.and_then(|s| s.avg_latency_ms).unwrap_or(f64::MAX);
+
+            failures(a)
+                .cmp(&failures(b))
+                .then(latency(a).total_cmp(&latency(b)))
+        });
+
+        throttled.sort_by_key(|(_, url)| self.nodes.get(url).and_then(|s| s.throttled_until));
+        available.extend(throttled);
+
+        if available.is_empty() {
+            open.sort_by_key(|(_, url)| self.nodes.get(url).and_then(|s| s.open_until));
+            return open;
+        }
+
+        available
+    }
+
+    pub fn default_path() -> PathBuf {
+        state_dir().join("node_health.json")
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path.as_ref())?;
+        serde_json::from_str(&content)
+            .map_err(|e| FanqieError::JsonParse(format!("节点状态文件格式错误: {}", e)))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| FanqieError::FileWrite(format!("创建目录失败: {}", e)))?;
+        }
+
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| FanqieError::JsonParse(format!("序列化节点状态失败: {}", e)))?;
+        fs::write(path.as_ref(), json)
+            .map_err(|e| FanqieError::FileWrite(format!("写入节点状态失败: {}", e)))
+    }
+}
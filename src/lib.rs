@@ -1,8 +1,17 @@
 pub mod config;
+pub mod settings;
+pub mod validate;
 pub mod api;
+pub mod health;
+pub mod device;
+pub mod probe;
+pub mod client;
 pub mod search;
 pub mod downloader;
+pub mod cache;
 pub mod export;
+pub mod manifest;
+pub mod update;
 pub mod cli;
 pub mod batch;
 pub mod error;
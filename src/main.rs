@@ -1,5 +1,5 @@
-use rs_fanqie_downloader::cli;
-use rs_fanqie_downloader::error::FanqieError;
+use fqdl::cli;
+use fqdl::error::FanqieError;
 
 #[tokio::main]
 async fn main() {
@@ -11,12 +11,20 @@ async fn main() {
         match e {
             FanqieError::ConfigNotFound(msg) => {
                 eprintln!("\n配置错误: {}", msg);
-                eprintln!("请确保 fanqie.json 配置文件存在");
+                eprintln!("请确保 fanqie.json 配置文件存在，或通过 --config / FQDL_CONFIG 指定");
             }
             FanqieError::AllNodesUnavailable => {
                 eprintln!("\n错误: 所有 API 节点都不可用");
                 eprintln!("请检查网络连接或稍后重试");
             }
+            FanqieError::RateLimited(msg) => {
+                eprintln!("\n错误: 请求被限流: {}", msg);
+                eprintln!("请稍后重试，或通过 --rate 降低请求频率");
+            }
+            FanqieError::Forbidden(msg) => {
+                eprintln!("\n错误: 无权访问: {}", msg);
+                eprintln!("请检查节点的 auth 令牌配置");
+            }
             FanqieError::BookNotFound(id) => {
                 eprintln!("\n错误: 书籍不存在或已下架: {}", id);
             }
@@ -0,0 +1,159 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::api::{BookInfo, ChapterContent, ChapterInfo};
+use crate::downloader::DownloadOptions;
+use crate::error::{FanqieError, Result};
+
+pub const MANIFEST_SUFFIX: &str = ".fqdl.json";
+
+/// 导出文件旁的清单文件 (`<导出文件名>.fqdl.json`)，记录生成该文件的书籍、章节、节点和下载选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DownloadManifest {
+    pub fqdl_version: String,
+    pub book_id: String,
+    pub book: BookInfo,
+    pub format: String,
+    pub file_name: String,
+    pub source_node: String,
+    pub options: DownloadOptions,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub chapters: Vec<ManifestChapter>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestChapter {
+    pub chapter_id: String,
+    pub title: String,
+    /// 章节正文的 SHA-256
+    pub sha256: String,
+    /// 所属分卷
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume_name: Option<String>,
+    /// 下载失败、导出文件中为占位内容
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub missing: bool,
+}
+
+impl DownloadManifest {
+    pub fn new(book_info: &BookInfo, options: &DownloadOptions, book_path: &Path, source_node: String) -> Self {
+        let now = Utc::now();
+        // 按章节分文件时导出的是目录，目录名中的 `.` 不是扩展名
+        let format = book_path
+            .extension()
+            .filter(|_| !options.dir_layout())
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_else(|| options.format.to_lowercase());
+
+        Self {
+            fqdl_version: crate::VERSION.to_string(),
+            book_id: options.book_id.clone(),
+            book: book_info.clone(),
+            format,
+            file_name: file_name_of(book_path),
+            source_node,
+            options: options.clone(),
+            created_at: now,
+            updated_at: now,
+            chapters: Vec::new(),
+        }
+    }
+
+    pub fn path_for(book_path: &Path) -> PathBuf {
+        let mut name = book_path.as_os_str().to_os_string();
+        name.push(MANIFEST_SUFFIX);
+        PathBuf::from(name)
+    }
+
+    pub fn is_manifest_path(path: &Path) -> bool {
+        path.to_string_lossy().ends_with(MANIFEST_SUFFIX)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let content = fs::read_to_string(path.as_ref())
+            .map_err(|e| FanqieError::ConfigLoad(format!("无法读取清单文件 {}: {}", path.as_ref().display(), e)))?;
+
+        serde_json::from_str(&content)
+            .map_err(|e| FanqieError::JsonParse(format!("清单文件格式错误 {}: {}", path.as_ref().display(), e)))
+    }
+
+    /// 写入到 `book_path` 对应的清单路径，并同步 `file_name`
+    pub fn save(&mut self, book_path: &Path) -> Result<PathBuf> {
+        self.file_name = file_name_of(book_path);
+
+        let manifest_path = Self::path_for(book_path);
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| FanqieError::JsonParse(format!("序列化清单失败: {}", e)))?;
+
+        fs::write(&manifest_path, json)
+            .map_err(|e| FanqieError::FileWrite(format!("写入清单失败: {}", e)))?;
+
+        Ok(manifest_path)
+    }
+
+    /// 清单所描述的导出文件路径
+    pub fn book_path(&self, manifest_path: &Path) -> PathBuf {
+        manifest_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .join(&self.file_name)
+    }
+
+    /// 追加已导出的章节，`missing` 中的章节标记为缺失
+    pub fn append_chapters(&mut self, chapters: &[ChapterContent], missing: &[ChapterInfo]) {
+        let missing: HashSet<&str> = missing.iter().map(|c| c.chapter_id.as_str()).collect();
+
+        self.chapters.extend(chapters.iter().map(|c| ManifestChapter {
+            chapter_id: c.chapter_id.clone(),
+            title: c.title.clone(),
+            sha256: content_hash(&c.content),
+            volume_name: c.volume_name.clone(),
+            missing: missing.contains(c.chapter_id.as_str()),
+        }));
+        self.updated_at = Utc::now();
+    }
+
+    pub fn last_chapter_id(&self) -> Option<&str> {
+        self.chapters.last().map(|c| c.chapter_id.as_str())
+    }
+
+    /// 末尾连续标记为缺失的章节数
+    pub fn trailing_missing(&self) -> usize {
+        self.chapters.iter().rev().take_while(|c| c.missing).count()
+    }
+
+    /// 在目录中查找指定书籍的清单文件
+    pub fn find_in_dir<P: AsRef<Path>>(dir: P, book_id: &str) -> Result<Vec<PathBuf>> {
+        let mut found = Vec::new();
+
+        for entry in fs::read_dir(dir.as_ref())? {
+            let path = entry?.path();
+            if !Self::is_manifest_path(&path) {
+                continue;
+            }
+            if let Ok(manifest) = Self::load(&path) {
+                if manifest.book_id == book_id {
+                    found.push(path);
+                }
+            }
+        }
+
+        found.sort();
+        Ok(found)
+    }
+}
+
+pub fn content_hash(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+fn file_name_of(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
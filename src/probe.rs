@@ -0,0 +1,246 @@
+use futures::future::join_all;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::api::{ApiClient, BookDetailResponse, ChapterContentResponse, DirectoryResponse, RawFullResponse, SearchResponse};
+use crate::config::ApiSource;
+use crate::error::{FanqieError, Result};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndpointKind {
+    Search,
+    Detail,
+    Content,
+    RawFull,
+}
+
+impl EndpointKind {
+    pub const ALL: [EndpointKind; 4] = [
+        EndpointKind::Search,
+        EndpointKind::Detail,
+        EndpointKind::Content,
+        EndpointKind::RawFull,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            EndpointKind::Search => "search",
+            EndpointKind::Detail => "detail",
+            EndpointKind::Content => "content",
+            EndpointKind::RawFull => "raw_full",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProbeOptions {
+    /// 测试搜索接口使用的关键词
+    pub keyword: String,
+    /// 测试详情、目录和整本接口使用的书籍ID
+    pub book_id: String,
+}
+
+impl Default for ProbeOptions {
+    fn default() -> Self {
+        Self {
+            keyword: "斗破苍穹".to_string(),
+            book_id: "7143038691944959011".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ProbeOutcome {
+    Ok(Duration),
+    Failed(String),
+    /// 节点不支持该接口 (如未开启整本下载)
+    Skipped,
+}
+
+impl ProbeOutcome {
+    pub fn is_ok(&self) -> bool {
+        matches!(self, ProbeOutcome::Ok(_))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeProbe {
+    pub base_url: String,
+    pub results: Vec<(EndpointKind, ProbeOutcome)>,
+}
+
+impl NodeProbe {
+    pub fn outcome(&self, kind: EndpointKind) -> Option<&ProbeOutcome> {
+        self.results.iter().find(|(k, _)| *k == kind).map(|(_, o)| o)
+    }
+
+    pub fn success_count(&self) -> usize {
+        self.results.iter().filter(|(_, o)| o.is_ok()).count()
+    }
+
+    pub fn failure_count(&self) -> usize {
+        self.results.iter().filter(|(_, o)| matches!(o, ProbeOutcome::Failed(_))).count()
+    }
+
+    /// 成功接口的平均延迟
+    pub fn avg_latency(&self) -> Option<Duration> {
+        let latencies: Vec<Duration> = self.results
+            .iter()
+            .filter_map(|(_, o)| match o {
+                ProbeOutcome::Ok(latency) => Some(*latency),
+                _ => None,
+            })
+            .collect();
+
+        if latencies.is_empty() {
+            return None;
+        }
+        Some(latencies.iter().sum::<Duration>() / latencies.len() as u32)
+    }
+}
+
+/// 向每个节点的各类接口发送一次请求，测量可用性和延迟
+pub struct NodeProber {
+    client: Arc<ApiClient>,
+    options: ProbeOptions,
+}
+
+impl NodeProber {
+    pub fn new(client: Arc<ApiClient>, options: ProbeOptions) -> Self {
+        Self { client, options }
+    }
+
+    /// 并发测试所有节点，返回按失败接口数量和平均延迟排序的结果
+    pub async fn run(&self) -> Vec<NodeProbe> {
+        let config = self.client.config();
+        let config_guard = config.read().await;
+        let sources = config_guard.api_sources.clone();
+        drop(config_guard);
+
+        let mut probes = join_all(sources.iter().map(|source| self.probe_node(source))).await;
+        rank(&mut probes);
+        probes
+    }
+
+    async fn probe_node(&self, source: &ApiSource) -> NodeProbe {
+        let mut results = Vec::new();
+
+        for kind in EndpointKind::ALL {
+            let outcome = match self.probe_endpoint(source, kind).await {
+                Ok(Some(latency)) => ProbeOutcome::Ok(latency),
+                Ok(None) => ProbeOutcome::Skipped,
+                Err(e) => ProbeOutcome::Failed(e.to_string()),
+            };
+            results.push((kind, outcome));
+        }
+
+        NodeProbe {
+            base_url: source.base_url.clone(),
+            results,
+        }
+    }
+
+    async fn probe_endpoint(&self, source: &ApiSource, kind: EndpointKind) -> Result<Option<Duration>> {
+        let config = self.client.config();
+        let endpoints = config.read().await.endpoints.clone();
+        let base_url = source.base_url.as_str();
+        let book_id = self.options.book_id.as_str();
+
+        let latency = match kind {
+            EndpointKind::Search => {
+                let params = HashMap::from([
+                    ("key", self.options.keyword.as_str()),
+                    ("tab_type", "3"),
+                    ("offset", "0"),
+                ]);
+                let (_, latency) = self.client
+                    .probe::<SearchResponse>(base_url, &endpoints.search, &params).await?;
+                latency
+            }
+            EndpointKind::Detail => {
+                let params = HashMap::from([("book_id", book_id)]);
+                let (_, latency) = self.client
+                    .probe::<BookDetailResponse>(base_url, &endpoints.detail, &params).await?;
+                latency
+            }
+            EndpointKind::Content => {
+                // 正文接口需要章节ID，先从同一节点的目录中取第一章
+                let params = HashMap::from([("fq_id", book_id)]);
+                let (directory, _) = self.client
+                    .probe::<DirectoryResponse>(base_url, &endpoints.directory, &params).await?;
+                let chapter_id = directory.data
+                    .and_then(|d| d.lists)
+                    .and_then(|lists| lists.into_iter().next())
+                    .map(|c| c.chapter_id)
+                    .ok_or_else(|| FanqieError::ChapterFetch("无法获取章节目录".to_string()))?;
+
+                let params = HashMap::from([("tab", "小说"), ("item_id", chapter_id.as_str())]);
+                let (_, latency) = self.client
+                    .probe::<ChapterContentResponse>(base_url, &endpoints.content, &params).await?;
+                latency
+            }
+            EndpointKind::RawFull => {
+                if !source.supports_full_download {
+                    return Ok(None);
+                }
+                let params = HashMap::from([("book_id", book_id)]);
+                let (_, latency) = self.client
+                    .probe::<RawFullResponse>(base_url, &endpoints.raw_full, &params).await?;
+                latency
+            }
+        };
+
+        Ok(Some(latency))
+    }
+}
+
+/// 失败接口少的节点在前 (跳过的接口不计入)，相同时平均延迟低的在前
+pub fn rank(probes: &mut [NodeProbe]) {
+    probes.sort_by(|a, b| {
+        a.failure_count()
+            .cmp(&b.failure_count())
+            .then(a.avg_latency().unwrap_or(Duration::MAX).cmp(&b.avg_latency().unwrap_or(Duration::MAX)))
+    });
+}
+
+pub fn format_probe_results(probes: &[NodeProbe]) -> String {
+    let mut output = String::new();
+
+    output.push_str(&format!("\n{:<4} {:<40}", "排名", "节点"));
+    for kind in EndpointKind::ALL {
+        output.push_str(&format!(" {:>10}", kind.label()));
+    }
+    output.push('\n');
+    output.push_str(&"-".repeat(90));
+    output.push('\n');
+
+    for (i, probe) in probes.iter().enumerate() {
+        output.push_str(&format!("{:<4} {:<40}", i + 1, probe.base_url));
+        for kind in EndpointKind::ALL {
+            let cell = match probe.outcome(kind) {
+                Some(ProbeOutcome::Ok(latency)) => format!("{}ms", latency.as_millis()),
+                Some(ProbeOutcome::Failed(_)) => "失败".to_string(),
+                Some(ProbeOutcome::Skipped) | None => "-".to_string(),
+            };
+            output.push_str(&format!(" {:>10}", cell));
+        }
+        output.push('\n');
+    }
+
+    let failures: Vec<String> = probes
+        .iter()
+        .flat_map(|p| p.results.iter().filter_map(move |(kind, outcome)| match outcome {
+            ProbeOutcome::Failed(e) => Some(format!("  {} {}: {}", p.base_url, kind.label(), e)),
+            _ => None,
+        }))
+        .collect();
+
+    if !failures.is_empty() {
+        output.push_str("\n失败详情:\n");
+        output.push_str(&failures.join("\n"));
+        output.push('\n');
+    }
+
+    output
+}
@@ -1,4 +1,4 @@
-use crate::api::{get_api_client, BookInfo};
+use crate::api::{get_api_client, ApiClient, BookInfo};
 use crate::error::{FanqieError, Result};
 
 pub struct SearchResult {
@@ -7,14 +7,12 @@ pub struct SearchResult {
 }
 
 pub async fn search(keyword: &str, offset: Option<i32>) -> Result<SearchResult> {
-    let client = get_api_client();
-    let response = client.search_books(keyword, offset.unwrap_or(0)).await?;
+    let client = get_api_client()?;
+    search_with(&client, keyword, offset).await
+}
 
-    if response.code != 200 {
-        return Err(FanqieError::ApiRequest(
-            format!("搜索失败: API 返回错误码 {}", response.code)
-        ));
-    }
+pub async fn search_with(client: &ApiClient, keyword: &str, offset: Option<i32>) -> Result<SearchResult> {
+    let response = client.search_books(keyword, offset.unwrap_or(0)).await?;
 
     let mut books = Vec::new();
 
@@ -74,13 +72,12 @@ pub fn format_search_results(books: &[BookInfo]) -> String {
 }
 
 pub async fn get_book_info(book_id: &str) -> Result<BookInfo> {
-    let client = get_api_client();
-    
+    let client = get_api_client()?;
+    get_book_info_with(&client, book_id).await
+}
+
+pub async fn get_book_info_with(client: &ApiClient, book_id: &str) -> Result<BookInfo> {
     let detail_response = client.get_book_detail(book_id).await?;
-    
-    if detail_response.code != 200 {
-        return Err(FanqieError::BookNotFound(book_id.to_string()));
-    }
 
     if let Some(data) = detail_response.data {
         if let Some(book_info) = data.data {
@@ -94,7 +91,7 @@ pub async fn get_book_info(book_id: &str) -> Result<BookInfo> {
 pub fn format_book_info(book: &BookInfo, chapter_count: Option<usize>) -> String {
     let mut result = String::new();
     result.push_str(&"=".repeat(50));
-    result.push_str("\n");
+    result.push('\n');
     result.push_str(&format!("书名: {}\n", book.book_name));
     result.push_str(&format!("作者: {}\n", book.author));
     
@@ -103,18 +100,18 @@ pub fn format_book_info(book: &BookInfo, chapter_count: Option<usize>) -> String
     }
     
     result.push_str(&"-".repeat(50));
-    result.push_str("\n");
+    result.push('\n');
     
     let abstract_text = book.get_abstract();
     if !abstract_text.is_empty() {
         result.push_str("简介:\n");
         let abstract_display = truncate_string(abstract_text, 200);
         result.push_str(&abstract_display);
-        result.push_str("\n");
+        result.push('\n');
     }
     
     result.push_str(&"=".repeat(50));
-    result.push_str("\n");
+    result.push('\n');
 
     result
 }
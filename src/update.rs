@@ -0,0 +1,282 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::api::{get_api_client, ApiClient, ChapterContent, ChapterInfo};
+use crate::downloader::{placeholder_text, DownloadOptions, Downloader};
+use crate::error::{FanqieError, Result};
+use crate::export::{
+    append_markdown, append_txt, chapter_file_name, export_dir, export_epub, export_html, read_epub_chapters,
+    read_html_chapters, remove_trailing_chapters,
+};
+use crate::manifest::{DownloadManifest, ManifestChapter};
+
+#[derive(Debug, Clone)]
+pub struct UpdateOptions {
+    /// 导出文件路径、清单文件路径或书籍ID
+    pub target: String,
+    /// 按书籍ID查找清单时搜索的目录
+    pub save_path: String,
+    pub use_cache: bool,
+    pub allow_partial: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct UpdateResult {
+    pub book_id: String,
+    pub output_path: PathBuf,
+    pub new_chapters: usize,
+}
+
+pub struct Updater {
+    client: Arc<ApiClient>,
+    options: UpdateOptions,
+}
+
+impl Updater {
+    pub fn new(client: Arc<ApiClient>, options: UpdateOptions) -> Self {
+        Self { client, options }
+    }
+
+    /// 找到要更新的清单文件: 目标为文件时使用其清单，否则视为书籍ID在保存目录中查找
+    fn resolve_manifests(&self) -> Result<Vec<PathBuf>> {
+        let target = Path::new(&self.options.target);
+
+        // 按章节分文件导出的目录，其清单在目录旁
+        if target.is_file() || (target.is_dir() && DownloadManifest::path_for(target).is_file()) {
+            let manifest_path = if DownloadManifest::is_manifest_path(target) {
+                target.to_path_buf()
+            } else {
+                DownloadManifest::path_for(target)
+            };
+
+            if !manifest_path.exists() {
+                return Err(FanqieError::Download(
+                    format!("未找到清单文件: {}", manifest_path.display())
+                ));
+            }
+            return Ok(vec![manifest_path]);
+        }
+
+        let manifests = DownloadManifest::find_in_dir(&self.options.save_path, &self.options.target)?;
+        if manifests.is_empty() {
+            return Err(FanqieError::Download(format!(
+                "在 {} 中未找到书籍 {} 的下载记录",
+                self.options.save_path, self.options.target
+            )));
+        }
+
+        Ok(manifests)
+    }
+
+    pub async fn run(&self) -> Result<Vec<UpdateResult>> {
+        let mut results = Vec::new();
+
+        for manifest_path in self.resolve_manifests()? {
+            results.push(self.update_one(&manifest_path).await?);
+        }
+
+        Ok(results)
+    }
+
+    async fn update_one(&self, manifest_path: &Path) -> Result<UpdateResult> {
+        let mut manifest = DownloadManifest::load(manifest_path)?;
+        let book_path = manifest.book_path(manifest_path);
+
+        if !book_path.exists() {
+            return Err(FanqieError::Download(
+                format!("导出文件不存在: {}", book_path.display())
+            ));
+        }
+
+        let save_dir = book_path
+            .parent()
+            .unwrap_or_else(|| Path::new("."))
+            .to_string_lossy()
+            .to_string();
+
+        let downloader = Downloader::new(self.client.clone(), DownloadOptions {
+            book_id: manifest.book_id.clone(),
+            save_path: save_dir.clone(),
+            format: manifest.format.clone(),
+            start_chapter: None,
+            end_chapter: None,
+            use_cache: self.options.use_cache,
+            allow_partial: self.options.allow_partial,
+            full_download: false,
+            filename_template: manifest.options.filename_template.clone(),
+            epub_theme: manifest.options.epub_theme.clone(),
+            epub_css: manifest.options.epub_css.clone(),
+            layout: manifest.options.layout.clone(),
+        });
+
+        let book_info = downloader.get_book_info().await?;
+        println!("正在更新: {} ({})", book_info.book_name, book_path.display());
+        if !book_info.get_status().is_empty() {
+            println!("状态: {}", book_info.get_status());
+        }
+
+        let chapters = downloader.get_chapters().await?;
+        let new_chapters = new_chapters_since(&manifest, &chapters);
+
+        if new_chapters.is_empty() {
+            println!("已是最新，共 {} 章", manifest.chapters.len());
+            return Ok(UpdateResult {
+                book_id: manifest.book_id,
+                output_path: book_path,
+                new_chapters: 0,
+            });
+        }
+
+        println!("发现 {} 个新章节: {} - {}",
+            new_chapters.len(),
+            new_chapters.first().map(|c| c.title.as_str()).unwrap_or(""),
+            new_chapters.last().map(|c| c.title.as_str()).unwrap_or("")
+        );
+
+        let downloaded = downloader.download_all_chapters(&new_chapters, None).await?;
+        let mut contents = downloaded.contents;
+
+        // 未允许部分下载时只写入第一个缺失章节之前的内容，下次更新从缺失处继续
+        if !self.options.allow_partial {
+            let missing: HashSet<&str> = downloaded.missing
+                .iter()
+                .map(|c| c.chapter_id.as_str())
+                .collect();
+            if let Some(first_missing) = contents.iter().position(|c| missing.contains(c.chapter_id.as_str())) {
+                contents.truncate(first_missing);
+            }
+        }
+
+        // 末尾缺失的章节会重新下载，写入前先从导出文件中去掉它们的占位内容
+        let stale = if contents.is_empty() {
+            Vec::new()
+        } else {
+            let kept = manifest.chapters.len() - manifest.trailing_missing();
+            manifest.chapters.split_off(kept)
+        };
+
+        let output_path = if contents.is_empty() {
+            book_path.clone()
+        } else if manifest.options.dir_layout() {
+            let kept = manifest.chapters.len();
+            for (index, chapter) in stale.iter().enumerate() {
+                let file = book_path.join(chapter_file_name(kept + index + 1, &chapter.title, &manifest.format));
+                if file.exists() {
+                    std::fs::remove_file(&file)
+                        .map_err(|e| FanqieError::FileWrite(format!("删除 {} 失败: {}", file.display(), e)))?;
+                }
+            }
+
+            // 已有章节的文件保留不动，索引按全部章节重新生成
+            let mut all_contents: Vec<ChapterContent> = manifest.chapters
+                .iter()
+                .map(|chapter| ChapterContent {
+                    chapter_id: chapter.chapter_id.clone(),
+                    title: chapter.title.clone(),
+                    content: String::new(),
+                    volume_name: chapter.volume_name.clone(),
+                })
+                .collect();
+            let existing = all_contents.len();
+            all_contents.extend(contents.iter().cloned());
+            export_dir(&book_info, &all_contents, &book_path, &manifest.format, existing)?;
+            book_path.clone()
+        } else if manifest.format == "txt" || manifest.format == "md" {
+            let last_volume = manifest.chapters.last().and_then(|c| c.volume_name.as_deref());
+            if !stale.is_empty() {
+                let placeholders: Vec<ChapterContent> = stale.iter().map(placeholder_content).collect();
+                remove_trailing_chapters(&book_path, &manifest.format, &placeholders, last_volume)?;
+            }
+            if manifest.format == "md" {
+                append_markdown(&book_path, &contents, last_volume)?;
+            } else {
+                append_txt(&book_path, &contents, last_volume)?;
+            }
+            book_path.clone()
+        } else {
+            // EPUB 和 HTML 读回已有章节后在原文件上整体重新生成，书名变化时也不改名
+            let mut all_contents = match manifest.format.as_str() {
+                "epub" => read_epub_chapters(&book_path)?,
+                "html" => read_html_chapters(&book_path)?,
+                other => return Err(FanqieError::UnsupportedFormat(other.to_string())),
+            };
+            let recorded = manifest.chapters.len() + stale.len();
+            if all_contents.len() != recorded {
+                return Err(FanqieError::Download(format!(
+                    "{} 中有 {} 章，与下载记录中的 {} 章不一致",
+                    book_path.display(), all_contents.len(), recorded
+                )));
+            }
+            all_contents.truncate(manifest.chapters.len());
+            for (content, chapter) in all_contents.iter_mut().zip(&manifest.chapters) {
+                content.chapter_id = chapter.chapter_id.clone();
+                content.volume_name = chapter.volume_name.clone();
+            }
+            all_contents.extend(contents.iter().cloned());
+            if manifest.format == "epub" {
+                export_epub(&book_info, &all_contents, &book_path, &downloader.epub_options(&book_info).await?)?;
+            } else {
+                export_html(&book_info, &all_contents, &book_path)?;
+            }
+            book_path.clone()
+        };
+
+        if !contents.is_empty() {
+            manifest.book = book_info.clone();
+            manifest.source_node = self.client.get_current_node().await;
+            manifest.append_chapters(&contents, &downloaded.missing);
+            manifest.save(&output_path)?;
+            println!("已追加 {} 章，保存至: {}", contents.len(), output_path.display());
+        }
+
+        downloader.report_missing(&downloaded.missing)?;
+        if downloaded.missing.is_empty() {
+            downloader.clear_cache()?;
+        }
+
+        Ok(UpdateResult {
+            book_id: manifest.book_id,
+            output_path,
+            new_chapters: contents.len(),
+        })
+    }
+}
+
+/// 目录中排在清单最后一个未缺失章节之后的章节，末尾缺失的章节会重新下载；
+/// 找不到该章节时，返回清单中未记录或未下载成功的全部章节
+fn new_chapters_since(manifest: &DownloadManifest, chapters: &[ChapterInfo]) -> Vec<ChapterInfo> {
+    let saved = &manifest.chapters[..manifest.chapters.len() - manifest.trailing_missing()];
+
+    if let Some(last) = saved.last() {
+        if let Some(position) = chapters.iter().position(|c| c.chapter_id == last.chapter_id) {
+            return chapters[position + 1..].to_vec();
+        }
+    }
+
+    let saved: HashSet<&str> = saved
+        .iter()
+        .map(|c| c.chapter_id.as_str())
+        .collect();
+
+    chapters
+        .iter()
+        .filter(|c| !saved.contains(c.chapter_id.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// 清单中缺失章节在导出文件中写入的占位内容
+fn placeholder_content(chapter: &ManifestChapter) -> ChapterContent {
+    ChapterContent {
+        chapter_id: chapter.chapter_id.clone(),
+        title: chapter.title.clone(),
+        content: placeholder_text(&chapter.chapter_id),
+        volume_name: chapter.volume_name.clone(),
+    }
+}
+
+pub async fn update_book(options: UpdateOptions) -> Result<Vec<UpdateResult>> {
+    let updater = Updater::new(get_api_client()?, options);
+    updater.run().await
+}
@@ -1,3 +1,5 @@
 pub mod rate_limiter;
+pub mod backoff;
 
 pub use rate_limiter::TokenBucket;
+pub use backoff::{parse_retry_after, retry_after_secs, Backoff};
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+use crate::config::ConfigParams;
+
+pub struct Backoff {
+    base: f64,
+    max: f64,
+    jitter: f64,
+}
+
+impl Backoff {
+    pub fn new(base: f64, max: f64, jitter: f64) -> Self {
+        Self {
+            base: base.max(0.0),
+            max: max.max(0.0),
+            jitter: jitter.clamp(0.0, 1.0),
+        }
+    }
+
+    pub fn from_params(params: &ConfigParams) -> Self {
+        Self::new(params.retry_backoff_base, params.retry_backoff_max, params.retry_jitter)
+    }
+
+    /// 第 `attempt` 次重试前的等待时间 (从 0 开始)，指数增长并叠加随机抖动
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let exp = self.base * 2f64.powi(attempt.min(31) as i32);
+        let capped = exp.min(self.max);
+        let factor = if self.jitter > 0.0 {
+            1.0 - self.jitter + rand::random::<f64>() * self.jitter * 2.0
+        } else {
+            1.0
+        };
+        Duration::try_from_secs_f64((capped * factor).max(0.0)).unwrap_or(Duration::MAX)
+    }
+}
+
+/// 解析 `Retry-After` 响应头，支持秒数和 HTTP 日期两种格式，已过去的日期视为无需等待
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return retry_after_secs(seconds);
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some((date.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or(Duration::ZERO))
+}
+
+/// 把服务器给出的等待秒数转换为 `Duration`，负数和非有限值视为无效，超出范围的值取最大值 (由调用方按 `max_retry_after` 截断)
+pub fn retry_after_secs(seconds: f64) -> Option<Duration> {
+    (seconds.is_finite() && seconds >= 0.0)
+        .then(|| Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX))
+}
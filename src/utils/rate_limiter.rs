@@ -1,26 +1,87 @@
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
+/// 被限流后速率最多降到初始速率的这一比例
+const MIN_RATE_FRACTION: f64 = 1.0 / 16.0;
+/// 每次成功请求恢复的速率，占初始速率的比例
+const RECOVERY_FRACTION: f64 = 0.02;
+/// 不限流时统计实际请求速率的时间窗口
+const OBSERVE_WINDOW: Duration = Duration::from_secs(2);
+
+/// 令牌桶限流器，当前速率可在被限流时降低，并随成功请求逐步恢复到初始速率。
+/// 用 [`TokenBucket::unlimited`] 创建的限流器平时不限流，被限流时以实际请求速率为基准降速，
+/// 恢复到该速率后重新不限流
 pub struct TokenBucket {
-    rate: f64,
+    /// 当前速率 (`f64` 的位表示)，不限流时为正无穷
+    rate: AtomicU64,
+    /// 恢复的目标速率 (`f64` 的位表示)
+    max_rate: AtomicU64,
+    unlimited: bool,
     capacity: f64,
     tokens: Arc<Mutex<f64>>,
     last_update: Arc<Mutex<Instant>>,
+    observed: std::sync::Mutex<ObservedRate>,
+}
+
+/// 不限流期间的请求计数: 当前窗口的起点和请求数，以及上一个窗口的速率
+struct ObservedRate {
+    since: Instant,
+    count: u32,
+    previous: f64,
+}
+
+impl ObservedRate {
+    fn new() -> Self {
+        Self { since: Instant::now(), count: 0, previous: 0.0 }
+    }
+
+    fn record(&mut self) {
+        let elapsed = self.since.elapsed();
+        if elapsed >= OBSERVE_WINDOW {
+            self.previous = self.count as f64 / elapsed.as_secs_f64();
+            self.since = Instant::now();
+            self.count = 0;
+        }
+        self.count += 1;
+    }
+
+    /// 每秒请求数，窗口不足 1 秒时按 1 秒计算
+    fn rate(&self) -> f64 {
+        let current = self.count as f64 / self.since.elapsed().as_secs_f64().max(1.0);
+        current.max(self.previous).max(1.0)
+    }
 }
 
 impl TokenBucket {
     pub fn new(rate: f64, capacity: f64) -> Self {
         Self {
-            rate,
+            rate: AtomicU64::new(rate.to_bits()),
+            max_rate: AtomicU64::new(rate.to_bits()),
+            unlimited: false,
             capacity,
             tokens: Arc::new(Mutex::new(capacity)),
             last_update: Arc::new(Mutex::new(Instant::now())),
+            observed: std::sync::Mutex::new(ObservedRate::new()),
+        }
+    }
+
+    /// 平时不限流、被限流后才降速的限流器，用于未设置 `api_rate_limit` 的情况
+    pub fn unlimited() -> Self {
+        Self {
+            unlimited: true,
+            ..Self::new(f64::INFINITY, 1.0)
         }
     }
 
     pub async fn acquire(&self) {
         loop {
+            if self.is_unthrottled() {
+                self.observed.lock().unwrap_or_else(|e| e.into_inner()).record();
+                return;
+            }
+
             {
                 let mut tokens = self.tokens.lock().await;
                 let mut last_update = self.last_update.lock().await;
@@ -28,7 +89,7 @@ impl TokenBucket {
                 let now = Instant::now();
                 let elapsed = now.duration_since(*last_update).as_secs_f64();
                 
-                *tokens = (*tokens + elapsed * self.rate).min(self.capacity);
+                *tokens = (*tokens + elapsed * self.get_rate()).min(self.capacity);
                 *last_update = now;
 
                 if *tokens >= 1.0 {
@@ -36,7 +97,7 @@ impl TokenBucket {
                     return;
                 }
 
-                let wait_time = (1.0 - *tokens) / self.rate;
+                let wait_time = (1.0 - *tokens) / self.get_rate();
                 drop(tokens);
                 drop(last_update);
 
@@ -46,13 +107,18 @@ impl TokenBucket {
     }
 
     pub async fn try_acquire(&self) -> bool {
+        if self.is_unthrottled() {
+            self.observed.lock().unwrap_or_else(|e| e.into_inner()).record();
+            return true;
+        }
+
         let mut tokens = self.tokens.lock().await;
         let mut last_update = self.last_update.lock().await;
 
         let now = Instant::now();
         let elapsed = now.duration_since(*last_update).as_secs_f64();
         
-        *tokens = (*tokens + elapsed * self.rate).min(self.capacity);
+        *tokens = (*tokens + elapsed * self.get_rate()).min(self.capacity);
         *last_update = now;
 
         if *tokens >= 1.0 {
@@ -64,7 +130,45 @@ impl TokenBucket {
     }
 
     pub fn get_rate(&self) -> f64 {
-        self.rate
+        f64::from_bits(self.rate.load(Ordering::Relaxed))
+    }
+
+    fn max_rate(&self) -> f64 {
+        f64::from_bits(self.max_rate.load(Ordering::Relaxed))
+    }
+
+    fn is_unthrottled(&self) -> bool {
+        self.get_rate().is_infinite()
+    }
+
+    /// 被限流时调用: 速率减半 (不低于初始速率的 1/16) 并清空已积累的令牌。
+    /// 不限流时先以最近的实际请求速率作为初始速率
+    pub async fn slow_down(&self) {
+        let mut tokens = self.tokens.lock().await;
+        let mut last_update = self.last_update.lock().await;
+        *tokens = 0.0;
+        *last_update = Instant::now();
+
+        let mut rate = self.get_rate();
+        if rate.is_infinite() {
+            let mut observed = self.observed.lock().unwrap_or_else(|e| e.into_inner());
+            rate = observed.rate();
+            *observed = ObservedRate::new();
+            self.max_rate.store(rate.to_bits(), Ordering::Relaxed);
+        }
+        let rate = (rate / 2.0).max(self.max_rate() * MIN_RATE_FRACTION);
+        self.rate.store(rate.to_bits(), Ordering::Relaxed);
+    }
+
+    /// 请求成功时调用: 速率按固定步长恢复，直到初始速率；不限流的限流器恢复后重新不限流
+    pub fn recover(&self) {
+        let rate = self.get_rate();
+        let max_rate = self.max_rate();
+        if rate < max_rate {
+            let rate = (rate + max_rate * RECOVERY_FRACTION).min(max_rate);
+            let rate = if self.unlimited && rate >= max_rate { f64::INFINITY } else { rate };
+            self.rate.store(rate.to_bits(), Ordering::Relaxed);
+        }
     }
 
     pub fn get_capacity(&self) -> f64 {
@@ -0,0 +1,412 @@
+use reqwest::header::{HeaderName, HeaderValue};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use url::Url;
+
+use crate::config::DIRECT_PROXY;
+use crate::error::{FanqieError, Result};
+use crate::export::EXPORT_FORMATS;
+use crate::settings::UserConfig;
+
+/// 配置中的一个问题，`path` 为出错位置，如 `$.api_sources[2].base_url`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigIssue {
+    pub path: String,
+    pub message: String,
+}
+
+impl ConfigIssue {
+    fn new(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            path: path.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// 数值参数的取值范围约束
+enum Range {
+    /// 大于 0
+    Positive,
+    /// 不小于 0
+    NonNegative,
+    /// 闭区间
+    Between(f64, f64),
+}
+
+const PARAM_RANGES: &[(&str, Range)] = &[
+    ("max_workers", Range::Positive),
+    ("max_retries", Range::Between(0.0, 20.0)),
+    ("retry_backoff_base", Range::Between(0.0, 60.0)),
+    ("retry_backoff_max", Range::Between(0.0, 600.0)),
+    ("retry_jitter", Range::Between(0.0, 1.0)),
+    ("request_timeout", Range::Positive),
+    ("request_rate_limit", Range::NonNegative),
+    ("connection_pool_size", Range::NonNegative),
+    ("api_rate_limit", Range::NonNegative),
+    ("rate_limit_window", Range::Positive),
+    ("async_batch_size", Range::Positive),
+    ("circuit_failure_threshold", Range::Positive),
+    ("circuit_cooldown", Range::NonNegative),
+    ("throttle_cooldown", Range::NonNegative),
+    ("max_retry_after", Range::NonNegative),
+];
+
+/// 只接受整数的参数
+const INTEGER_PARAMS: &[&str] = &[
+    "max_workers",
+    "max_retries",
+    "request_timeout",
+    "connection_pool_size",
+    "api_rate_limit",
+    "async_batch_size",
+    "circuit_failure_threshold",
+    "circuit_cooldown",
+    "throttle_cooldown",
+    "max_retry_after",
+];
+
+/// 校验 fanqie.json 配置文件，返回发现的所有问题
+pub fn validate_config_file<P: AsRef<Path>>(path: P) -> Result<Vec<ConfigIssue>> {
+    let content = fs::read_to_string(path.as_ref())
+        .map_err(|e| FanqieError::ConfigLoad(format!("无法读取配置文件: {}", e)))?;
+    Ok(validate_config_str(&content))
+}
+
+pub fn validate_config_str(content: &str) -> Vec<ConfigIssue> {
+    match serde_json::from_str::<Value>(content) {
+        Ok(json) => validate_config_value(&json),
+        Err(e) => vec![ConfigIssue::new(
+            "$",
+            format!("JSON 语法错误 (第 {} 行第 {} 列): {}", e.line(), e.column(), e),
+        )],
+    }
+}
+
+pub fn validate_config_value(json: &Value) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+
+    if !json.is_object() {
+        issues.push(ConfigIssue::new("$", "顶层应为对象"));
+        return issues;
+    }
+
+    for key in ["version", "updated_at"] {
+        if !json.get(key).is_some_and(Value::is_string) {
+            issues.push(ConfigIssue::new(format!("$.{}", key), "缺少该字段或不是字符串"));
+        }
+    }
+
+    validate_api_sources(json.get("api_sources"), &mut issues);
+    validate_endpoints(json.get("endpoints"), &mut issues);
+    validate_params(json.get("config"), &mut issues);
+
+    issues
+}
+
+fn validate_api_sources(sources: Option<&Value>, issues: &mut Vec<ConfigIssue>) {
+    let Some(sources) = sources else {
+        issues.push(ConfigIssue::new("$.api_sources", "缺少节点列表"));
+        return;
+    };
+    let Some(sources) = sources.as_array() else {
+        issues.push(ConfigIssue::new("$.api_sources", "应为数组"));
+        return;
+    };
+    if sources.is_empty() {
+        issues.push(ConfigIssue::new("$.api_sources", "至少需要一个节点"));
+    }
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+
+    for (index, source) in sources.iter().enumerate() {
+        let path = format!("$.api_sources[{}]", index);
+
+        if !source.is_object() {
+            issues.push(ConfigIssue::new(&path, "应为对象"));
+            continue;
+        }
+
+        match source.get("base_url").and_then(Value::as_str) {
+            None => issues.push(ConfigIssue::new(format!("{}.base_url", path), "缺少节点地址或不是字符串")),
+            Some(base_url) => {
+                let url_path = format!("{}.base_url", path);
+                if let Some(problem) = check_url(base_url, &["http", "https"]) {
+                    issues.push(ConfigIssue::new(&url_path, problem));
+                } else if base_url.ends_with('/') {
+                    issues.push(ConfigIssue::new(&url_path, "不应以 / 结尾，端点路径会直接拼接在节点地址之后"));
+                }
+
+                let key = base_url.trim_end_matches('/').to_lowercase();
+                if let Some(first) = seen.get(&key) {
+                    issues.push(ConfigIssue::new(&url_path, format!("与 $.api_sources[{}] 重复", first)));
+                } else {
+                    seen.insert(key, index);
+                }
+            }
+        }
+
+        if let Some(value) = source.get("supports_full_download") {
+            if !value.is_boolean() {
+                issues.push(ConfigIssue::new(format!("{}.supports_full_download", path), "应为 true 或 false"));
+            }
+        }
+
+        if let Some(value) = source.get("request_rate_limit").filter(|v| !v.is_null()) {
+            check_number(&format!("{}.request_rate_limit", path), value, &Range::NonNegative, issues);
+        }
+
+        if let Some(proxy) = source.get("proxy").filter(|v| !v.is_null()) {
+            check_proxy(&format!("{}.proxy", path), proxy, true, issues);
+        }
+
+        if let Some(headers) = source.get("headers") {
+            check_headers(&format!("{}.headers", path), headers, issues);
+        }
+
+        if let Some(auth) = source.get("auth").filter(|v| !v.is_null()) {
+            check_auth(&format!("{}.auth", path), auth, issues);
+        }
+    }
+}
+
+fn validate_endpoints(endpoints: Option<&Value>, issues: &mut Vec<ConfigIssue>) {
+    let Some(endpoints) = endpoints else {
+        issues.push(ConfigIssue::new("$.endpoints", "缺少端点配置"));
+        return;
+    };
+    let Some(endpoints) = endpoints.as_object() else {
+        issues.push(ConfigIssue::new("$.endpoints", "应为对象"));
+        return;
+    };
+
+    for (name, endpoint) in endpoints {
+        let path = format!("$.endpoints.{}", name);
+        match endpoint.as_str() {
+            None => issues.push(ConfigIssue::new(path, "应为字符串")),
+            Some(endpoint) if !endpoint.starts_with('/') => {
+                issues.push(ConfigIssue::new(path, format!("端点路径应以 / 开头: {}", endpoint)));
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+fn validate_params(params: Option<&Value>, issues: &mut Vec<ConfigIssue>) {
+    let Some(params) = params else {
+        issues.push(ConfigIssue::new("$.config", "缺少下载配置"));
+        return;
+    };
+    let Some(params) = params.as_object() else {
+        issues.push(ConfigIssue::new("$.config", "应为对象"));
+        return;
+    };
+
+    for (name, range) in PARAM_RANGES {
+        let Some(value) = params.get(*name) else {
+            continue;
+        };
+        let path = format!("$.config.{}", name);
+
+        if INTEGER_PARAMS.contains(name) && value.is_number() && value.as_u64().is_none() {
+            issues.push(ConfigIssue::new(path, format!("应为非负整数 (当前为 {})", value)));
+        } else {
+            check_number(&path, value, range, issues);
+        }
+    }
+
+    let backoff_base = params.get("retry_backoff_base").and_then(Value::as_f64);
+    let backoff_max = params.get("retry_backoff_max").and_then(Value::as_f64);
+    if let (Some(base), Some(max)) = (backoff_base, backoff_max) {
+        if max < base {
+            issues.push(ConfigIssue::new("$.config.retry_backoff_max", "不应小于 retry_backoff_base"));
+        }
+    }
+
+    if let Some(value) = params.get("download_enabled") {
+        if !value.is_boolean() {
+            issues.push(ConfigIssue::new("$.config.download_enabled", "应为 true 或 false"));
+        }
+    }
+
+    if let Some(proxy) = params.get("proxy").filter(|v| !v.is_null()) {
+        check_proxy("$.config.proxy", proxy, false, issues);
+    }
+
+    if let Some(headers) = params.get("headers") {
+        check_headers("$.config.headers", headers, issues);
+    }
+
+    if let Some(user_agents) = params.get("user_agents") {
+        match user_agents.as_array() {
+            None => issues.push(ConfigIssue::new("$.config.user_agents", "应为字符串数组")),
+            Some(user_agents) => {
+                for (index, user_agent) in user_agents.iter().enumerate() {
+                    let valid = user_agent
+                        .as_str()
+                        .is_some_and(|ua| !ua.trim().is_empty() && HeaderValue::from_str(ua).is_ok());
+                    if !valid {
+                        issues.push(ConfigIssue::new(
+                            format!("$.config.user_agents[{}]", index),
+                            "应为非空且可作为请求头的字符串",
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(rotation) = params.get("user_agent_rotation") {
+        if !matches!(rotation.as_str(), Some("per_client" | "per_request")) {
+            issues.push(ConfigIssue::new("$.config.user_agent_rotation", "应为 per_client 或 per_request"));
+        }
+    }
+
+    if let Some(no_proxy) = params.get("no_proxy") {
+        match no_proxy.as_array() {
+            None => issues.push(ConfigIssue::new("$.config.no_proxy", "应为字符串数组")),
+            Some(hosts) => {
+                for (index, host) in hosts.iter().enumerate() {
+                    if host.as_str().is_none_or(|h| h.trim().is_empty()) {
+                        issues.push(ConfigIssue::new(format!("$.config.no_proxy[{}]", index), "应为非空字符串"));
+                    }
+                }
+            }
+        }
+    }
+
+    for key in ["throttle_codes", "device_invalid_codes"] {
+        let Some(codes) = params.get(key) else {
+            continue;
+        };
+        match codes.as_array() {
+            None => issues.push(ConfigIssue::new(format!("$.config.{}", key), "应为整数数组")),
+            Some(codes) => {
+                for (index, code) in codes.iter().enumerate() {
+                    if code.as_i64().is_none_or(|c| i32::try_from(c).is_err()) {
+                        issues.push(ConfigIssue::new(format!("$.config.{}[{}]", key, index), "应为整数"));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 校验用户配置 config.toml，问题路径为 TOML 中的键名
+pub fn validate_user_config<P: AsRef<Path>>(path: P) -> Result<Vec<ConfigIssue>> {
+    let user = match UserConfig::load(path.as_ref()) {
+        Ok(user) => user,
+        Err(e) => return Ok(vec![ConfigIssue::new("config.toml", e.to_string())]),
+    };
+
+    let mut issues = Vec::new();
+
+    if let Some(format) = &user.format {
+        if !EXPORT_FORMATS.contains(&format.to_lowercase().as_str()) {
+            issues.push(ConfigIssue::new("format", format!("不支持的导出格式: {}", format)));
+        }
+    }
+    if user.concurrency == Some(0) {
+        issues.push(ConfigIssue::new("concurrency", "应大于 0"));
+    }
+    if let Some(template) = &user.filename_template {
+        if template.trim().is_empty() {
+            issues.push(ConfigIssue::new("filename_template", "不能为空"));
+        }
+    }
+    if let Some(proxy) = &user.proxy {
+        if let Some(problem) = check_url(proxy, PROXY_SCHEMES) {
+            issues.push(ConfigIssue::new("proxy", problem));
+        }
+    }
+
+    Ok(issues)
+}
+
+fn check_headers(path: &str, headers: &Value, issues: &mut Vec<ConfigIssue>) {
+    let Some(headers) = headers.as_object() else {
+        issues.push(ConfigIssue::new(path, "应为对象"));
+        return;
+    };
+
+    for (name, value) in headers {
+        let header_path = format!("{}.{}", path, name);
+        if HeaderName::from_bytes(name.as_bytes()).is_err() {
+            issues.push(ConfigIssue::new(header_path, "请求头名称无效"));
+        } else if value.as_str().is_none_or(|v| HeaderValue::from_str(v).is_err()) {
+            issues.push(ConfigIssue::new(header_path, "请求头的值应为不含换行的字符串"));
+        }
+    }
+}
+
+fn check_auth(path: &str, auth: &Value, issues: &mut Vec<ConfigIssue>) {
+    let required: &[&str] = match auth.get("type").and_then(Value::as_str) {
+        Some("bearer") => &["token"],
+        Some("query") => &["param", "token"],
+        _ => {
+            issues.push(ConfigIssue::new(format!("{}.type", path), "应为 bearer 或 query"));
+            return;
+        }
+    };
+
+    for field in required {
+        if auth.get(*field).and_then(Value::as_str).is_none_or(str::is_empty) {
+            issues.push(ConfigIssue::new(format!("{}.{}", path, field), "缺少该字段或为空"));
+        }
+    }
+}
+
+const PROXY_SCHEMES: &[&str] = &["http", "https", "socks5", "socks5h"];
+
+/// `allow_direct` 为真时接受 `direct` (节点级设置，表示不使用代理)
+fn check_proxy(path: &str, value: &Value, allow_direct: bool, issues: &mut Vec<ConfigIssue>) {
+    match value.as_str() {
+        None => issues.push(ConfigIssue::new(path, "应为字符串")),
+        Some(DIRECT_PROXY) if allow_direct => {}
+        Some(proxy) => {
+            if let Some(problem) = check_url(proxy, PROXY_SCHEMES) {
+                issues.push(ConfigIssue::new(path, problem));
+            }
+        }
+    }
+}
+
+fn check_url(value: &str, schemes: &[&str]) -> Option<String> {
+    match Url::parse(value) {
+        Err(e) => Some(format!("无效的 URL {}: {}", value, e)),
+        Ok(url) if !schemes.contains(&url.scheme()) => {
+            Some(format!("不支持的协议 {}，应为 {}", url.scheme(), schemes.join("/")))
+        }
+        Ok(url) if url.host_str().is_none() => Some(format!("URL 缺少主机名: {}", value)),
+        Ok(_) => None,
+    }
+}
+
+fn check_number(path: &str, value: &Value, range: &Range, issues: &mut Vec<ConfigIssue>) {
+    let Some(number) = value.as_f64() else {
+        issues.push(ConfigIssue::new(path, "应为数字"));
+        return;
+    };
+
+    let problem = match range {
+        Range::Positive if number <= 0.0 => Some("应大于 0".to_string()),
+        Range::NonNegative if number < 0.0 => Some("不能为负数".to_string()),
+        Range::Between(min, max) if number < *min || number > *max => {
+            Some(format!("应在 {} 到 {} 之间", min, max))
+        }
+        _ => None,
+    };
+
+    if let Some(problem) = problem {
+        issues.push(ConfigIssue::new(path, format!("{} (当前为 {})", problem, number)));
+    }
+}
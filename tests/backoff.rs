@@ -0,0 +1,30 @@
+use std::time::Duration;
+
+use fqdl::utils::Backoff;
+
+#[test]
+fn delay_doubles_per_attempt_up_to_the_cap() {
+    let backoff = Backoff::new(0.5, 3.0, 0.0);
+
+    assert_eq!(backoff.delay(0), Duration::from_millis(500));
+    assert_eq!(backoff.delay(1), Duration::from_secs(1));
+    assert_eq!(backoff.delay(2), Duration::from_secs(2));
+    assert_eq!(backoff.delay(3), Duration::from_secs(3));
+    assert_eq!(backoff.delay(40), Duration::from_secs(3));
+}
+
+#[test]
+fn jitter_stays_within_its_fraction_of_the_delay() {
+    let backoff = Backoff::new(1.0, 8.0, 0.25);
+
+    for _ in 0..100 {
+        let delay = backoff.delay(1).as_secs_f64();
+        assert!((1.5..=2.5).contains(&delay), "{}", delay);
+    }
+}
+
+#[test]
+fn huge_backoff_saturates_instead_of_panicking() {
+    let backoff = Backoff::new(1e30, 1e30, 0.5);
+    assert_eq!(backoff.delay(10), Duration::MAX);
+}
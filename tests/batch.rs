@@ -0,0 +1,42 @@
+mod common;
+
+use serde_json::json;
+use std::io::Read;
+
+use common::{temp_dir, test_config, MockBook, MockServer};
+use fqdl::batch::BatchOptions;
+use fqdl::client::FanqieClient;
+
+#[tokio::test]
+async fn batch_applies_shared_download_options_to_every_book() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("3004", 2));
+    server.add_book(MockBook::sample("3005", 3));
+
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({ "max_retries": 0 }))).unwrap();
+    let dir = temp_dir("batch");
+
+    let options = BatchOptions {
+        book_ids: vec!["3004".to_string(), "3005".to_string()],
+        save_path: dir.to_string_lossy().to_string(),
+        format: "epub".to_string(),
+        max_concurrent: 2,
+        use_cache: false,
+        allow_partial: false,
+        full_download: false,
+        filename_template: Some("{book_id}".to_string()),
+        epub_theme: Some("vertical".to_string()),
+        epub_css: None,
+        layout: None,
+    };
+    let results = client.batch(options).run().await.unwrap();
+
+    assert!(results.iter().all(|r| r.success), "{:?}", results);
+    for book_id in ["3004", "3005"] {
+        let epub = std::fs::File::open(dir.join(format!("{}.epub", book_id))).unwrap();
+        let mut archive = zip::ZipArchive::new(epub).unwrap();
+        let mut css = String::new();
+        archive.by_name("OEBPS/stylesheet.css").unwrap().read_to_string(&mut css).unwrap();
+        assert!(css.contains("writing-mode: vertical-rl"), "{} 未使用主题", book_id);
+    }
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use serde_json::json;
+
+use common::{download_options, temp_dir, test_config, MockBook, MockServer};
+use fqdl::api::ChapterContent;
+use fqdl::cache::ChapterCache;
+use fqdl::client::FanqieClient;
+use fqdl::downloader::Downloader;
+
+#[tokio::test]
+async fn resumes_from_cached_chapters() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("3101", 6));
+
+    // 缓存放在临时目录，以免写入用户缓存
+    let cache_root = temp_dir("cache-root");
+
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+
+    // 模拟中断: 前 3 章已在缓存中
+    let cache = ChapterCache::new(&cache_root, "3101");
+    for i in 1..=3 {
+        cache.store(&ChapterContent {
+            chapter_id: format!("3101{:04}", i),
+            title: format!("第{}章 测试章节", i),
+            content: format!("缓存中的第{}章。", i),
+            volume_name: None,
+        })
+        .unwrap();
+    }
+
+    let dir = temp_dir("cache");
+    let mut options = download_options("3101", &dir, "txt");
+    options.use_cache = true;
+    let path = Downloader::with_cache_root(client.api(), options, &cache_root).download_book().await.unwrap();
+
+    // 只请求缓存中没有的章节，导出成功后清理缓存
+    assert_eq!(server.hits("/api/content"), 3);
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains("缓存中的第1章。"));
+    assert!(text.contains("第6章第一段。"));
+    assert!(!cache.dir().exists());
+}
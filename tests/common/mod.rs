@@ -0,0 +1,580 @@
+//! 集成测试用的本地番茄 API 模拟服务器
+//!
+//! 服务器运行在独立线程的 tokio 运行时上，从内置的书籍数据响应
+//! `/api/search`、`/api/detail`、`/api/book`、`/api/directory`、`/api/content`、`/api/raw_full`
+//! 以及 iOS 正文和设备注册相关接口，
+//! 并可按接口注入延迟、5xx 错误、429 限流、格式错误的 JSON 和不完整的数据。
+//! `benches/` 中的基准测试通过 `#[path]` 引用同一份实现。
+
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use fqdl::config::{AppConfig, FanqieJson};
+use fqdl::downloader::DownloadOptions;
+
+#[derive(Debug, Clone)]
+pub struct MockChapter {
+    pub chapter_id: String,
+    pub title: String,
+    pub content: String,
+    pub volume_name: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct MockBook {
+    pub book_id: String,
+    pub book_name: String,
+    pub author: String,
+    pub abstract_text: String,
+    pub creation_status: String,
+    /// 封面地址，可指向本服务器的 `/cover.gif`
+    pub cover: Option<String>,
+    pub chapters: Vec<MockChapter>,
+}
+
+impl MockBook {
+    /// 生成 `chapter_count` 章、平均分为两卷的测试书籍
+    pub fn sample(book_id: &str, chapter_count: usize) -> Self {
+        let chapters = (1..=chapter_count)
+            .map(|i| MockChapter {
+                chapter_id: format!("{}{:04}", book_id, i),
+                title: format!("第{}章 测试章节", i),
+                content: format!("第{}章第一段。\n第{}章第二段。", i, i),
+                volume_name: if i <= chapter_count.div_ceil(2) {
+                    "第一卷".to_string()
+                } else {
+                    "第二卷".to_string()
+                },
+            })
+            .collect();
+
+        Self {
+            book_id: book_id.to_string(),
+            book_name: format!("测试书籍{}", book_id),
+            author: "测试作者".to_string(),
+            abstract_text: "这是一本用于测试的书。".to_string(),
+            creation_status: "1".to_string(),
+            cover: None,
+            chapters,
+        }
+    }
+
+    fn info_json(&self) -> Value {
+        json!({
+            "book_id": self.book_id,
+            "book_name": self.book_name,
+            "author": self.author,
+            "abstract": self.abstract_text,
+            "creation_status": self.creation_status,
+            "cover": self.cover,
+            "word_count": self.chapters.iter().map(|c| c.content.chars().count()).sum::<usize>(),
+            "chapter_count": self.chapters.len(),
+        })
+    }
+}
+
+/// 单个接口的故障注入设置
+#[derive(Debug, Clone, Default)]
+pub struct Behavior {
+    /// 每次响应前的延迟
+    pub latency: Duration,
+    /// 这些章节ID的正文请求改用对应的延迟，用于模拟长尾请求
+    pub slow_items: HashMap<String, Duration>,
+    /// 前 `fail_first` 次请求返回 `fail_status`
+    pub fail_first: usize,
+    pub fail_status: u16,
+    /// 设置后失败的请求改为返回 HTTP 200 和该 API 错误码
+    pub fail_code: Option<i32>,
+    /// 设置后失败的请求改为返回 HTTP 200 和该响应体，优先于 `fail_code`
+    pub fail_body: Option<Value>,
+    /// 失败的请求附带的 `Retry-After` 响应头
+    pub retry_after: Option<String>,
+    /// 所有请求都返回该状态码
+    pub always_status: Option<u16>,
+    /// 返回无法解析的 JSON
+    pub malformed: bool,
+    /// 列表类接口只返回前 n 项
+    pub partial: Option<usize>,
+    /// 这些章节ID的正文请求总是返回 500，设置在 `/api/content` 上时同样作用于 iOS 正文接口
+    pub failing_items: HashSet<String>,
+    /// 这些章节ID的正文返回空白，作用于设置所在的正文接口 (`/api/content`、`/api/ios/content` 或 `/api/raw_full`)
+    pub blank_items: HashSet<String>,
+}
+
+#[derive(Default)]
+struct State {
+    books: HashMap<String, MockBook>,
+    behaviors: HashMap<String, Behavior>,
+    hits: HashMap<String, usize>,
+    requests: Vec<RecordedRequest>,
+    /// `/api/ios/register` 发放过的设备ID
+    devices: Vec<String>,
+}
+
+/// 收到的请求，头部名称均为小写
+#[derive(Debug, Clone)]
+pub struct RecordedRequest {
+    /// 以代理身份收到请求 (请求行为绝对 URL) 时的目标主机
+    pub proxied_host: Option<String>,
+    pub path: String,
+    pub query: HashMap<String, String>,
+    pub headers: HashMap<String, String>,
+}
+
+impl RecordedRequest {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).map(String::as_str)
+    }
+}
+
+/// 以代理身份收到的请求
+#[derive(Debug, Clone)]
+pub struct ProxiedRequest {
+    pub host: String,
+    pub proxy_authorization: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct MockServer {
+    pub base_url: String,
+    state: Arc<Mutex<State>>,
+}
+
+impl MockServer {
+    pub fn start() -> Self {
+        let state = Arc::new(Mutex::new(State::default()));
+        let (tx, rx) = std::sync::mpsc::channel();
+
+        let server_state = state.clone();
+        std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(2)
+                .enable_all()
+                .build()
+                .unwrap();
+
+            runtime.block_on(async move {
+                let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+                tx.send(listener.local_addr().unwrap()).unwrap();
+
+                loop {
+                    let Ok((socket, _)) = listener.accept().await else {
+                        continue;
+                    };
+                    tokio::spawn(handle_connection(socket, server_state.clone()));
+                }
+            });
+        });
+
+        let addr = rx.recv().unwrap();
+        Self {
+            base_url: format!("http://{}", addr),
+            state,
+        }
+    }
+
+    pub fn add_book(&self, book: MockBook) {
+        self.state.lock().unwrap().books.insert(book.book_id.clone(), book);
+    }
+
+    pub fn set_behavior(&self, path: &str, behavior: Behavior) {
+        self.state.lock().unwrap().behaviors.insert(path.to_string(), behavior);
+    }
+
+    pub fn hits(&self, path: &str) -> usize {
+        self.state.lock().unwrap().hits.get(path).copied().unwrap_or(0)
+    }
+
+    /// 所有接口收到的请求总数
+    pub fn total_hits(&self) -> usize {
+        self.state.lock().unwrap().hits.values().sum()
+    }
+
+    /// 把本服务器当作 HTTP 代理使用时收到的请求
+    pub fn proxied_requests(&self) -> Vec<ProxiedRequest> {
+        self.requests()
+            .into_iter()
+            .filter_map(|r| {
+                let proxy_authorization = r.header("proxy-authorization").map(str::to_string);
+                r.proxied_host.map(|host| ProxiedRequest { host, proxy_authorization })
+            })
+            .collect()
+    }
+
+    /// 按到达顺序返回收到的全部请求
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    pub fn devices(&self) -> Vec<String> {
+        self.state.lock().unwrap().devices.clone()
+    }
+
+    pub fn reset_hits(&self) {
+        self.state.lock().unwrap().hits.clear();
+    }
+}
+
+/// 一个不会接受连接的节点地址
+pub fn dead_node() -> String {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{}", addr)
+}
+
+/// 构建指向给定节点的测试配置，关闭限流、缩短重试等待
+pub fn test_config(nodes: &[&str], overrides: Value) -> AppConfig {
+    let mut params = json!({
+        "max_workers": 8,
+        "max_retries": 2,
+        "retry_backoff_base": 0.01,
+        "retry_backoff_max": 0.05,
+        "retry_jitter": 0.0,
+        "request_timeout": 5,
+        "request_rate_limit": 0.0,
+        "api_rate_limit": 0,
+        "connection_pool_size": 0,
+    });
+    if let (Some(params), Some(overrides)) = (params.as_object_mut(), overrides.as_object()) {
+        for (key, value) in overrides {
+            params.insert(key.clone(), value.clone());
+        }
+    }
+
+    let sources: Vec<Value> = nodes.iter().map(|url| json!({ "base_url": url })).collect();
+    let json: FanqieJson = serde_json::from_value(json!({
+        "version": "test",
+        "updated_at": "",
+        "api_sources": sources,
+        "endpoints": {},
+        "config": params,
+    }))
+    .unwrap();
+
+    AppConfig::new(json, PathBuf::from("test"))
+}
+
+/// 下载到 `save_path` 的选项，不使用章节缓存
+pub fn download_options(book_id: &str, save_path: impl AsRef<Path>, format: &str) -> DownloadOptions {
+    DownloadOptions {
+        book_id: book_id.to_string(),
+        save_path: save_path.as_ref().to_string_lossy().to_string(),
+        format: format.to_string(),
+        start_chapter: None,
+        end_chapter: None,
+        use_cache: false,
+        allow_partial: false,
+        full_download: false,
+        filename_template: None,
+        epub_theme: None,
+        epub_css: None,
+        layout: None,
+    }
+}
+
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("fqdl-test-{}-{}", name, std::process::id()));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// `/cover.gif` 返回的封面内容，只需文件头能被识别为 GIF
+pub const COVER_GIF: &str = "GIF89a-mock-cover";
+
+async fn handle_connection(mut socket: TcpStream, state: Arc<Mutex<State>>) {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    loop {
+        let header_end = loop {
+            if let Some(pos) = find_header_end(&buffer) {
+                break pos;
+            }
+            match socket.read(&mut chunk).await {
+                Ok(0) | Err(_) => return,
+                Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+            }
+        };
+
+        let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+        buffer.drain(..header_end + 4);
+
+        let target = head
+            .lines()
+            .next()
+            .and_then(|line| line.split_whitespace().nth(1))
+            .unwrap_or("/")
+            .to_string();
+
+        let headers: HashMap<String, String> = head
+            .lines()
+            .skip(1)
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_lowercase(), value.trim().to_string()))
+            .collect();
+
+        let (status, body, latency) = respond(&target, headers, &state);
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+
+        let retry_after = match retry_after(&target, status, &state) {
+            Some(value) => format!("Retry-After: {}\r\n", value),
+            None => String::new(),
+        };
+        let content_type = if target.split('?').next().unwrap_or_default().ends_with(".gif") {
+            "image/gif"
+        } else {
+            "application/json"
+        };
+        let response = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n{}Connection: keep-alive\r\n\r\n{}",
+            status,
+            reason(status),
+            content_type,
+            body.len(),
+            retry_after,
+            body
+        );
+        if socket.write_all(response.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+fn find_header_end(buffer: &[u8]) -> Option<usize> {
+    buffer.windows(4).position(|w| w == b"\r\n\r\n")
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        502 => "Bad Gateway",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+/// 注入的失败响应需要附带的 `Retry-After`
+fn retry_after(target: &str, status: u16, state: &Arc<Mutex<State>>) -> Option<String> {
+    if status == 200 {
+        return None;
+    }
+    let path = target.split('?').next().unwrap_or(target);
+    let path = url::Url::parse(path).map(|u| u.path().to_string()).unwrap_or_else(|_| path.to_string());
+    state.lock().unwrap().behaviors.get(&path)?.retry_after.clone()
+}
+
+fn respond(
+    target: &str,
+    headers: HashMap<String, String>,
+    state: &Arc<Mutex<State>>,
+) -> (u16, String, Duration) {
+    let proxied = target.starts_with("http://");
+    let url = if proxied {
+        url::Url::parse(target).unwrap()
+    } else {
+        url::Url::parse(&format!("http://mock{}", target)).unwrap()
+    };
+    let path = url.path().to_string();
+    let query: HashMap<String, String> = url.query_pairs().into_owned().collect();
+
+    let mut state = state.lock().unwrap();
+    state.requests.push(RecordedRequest {
+        proxied_host: proxied.then(|| url.host_str().unwrap_or_default().to_string()),
+        path: path.clone(),
+        query: query.clone(),
+        headers,
+    });
+    let hit = {
+        let counter = state.hits.entry(path.clone()).or_insert(0);
+        *counter += 1;
+        *counter
+    };
+    let behavior = state.behaviors.get(&path).cloned().unwrap_or_default();
+
+    if let Some(status) = behavior.always_status {
+        return (status, json!({ "code": status }).to_string(), behavior.latency);
+    }
+    if hit <= behavior.fail_first {
+        if let Some(body) = &behavior.fail_body {
+            return (200, body.to_string(), behavior.latency);
+        }
+        if let Some(code) = behavior.fail_code {
+            return (200, json!({ "code": code, "message": "请求过于频繁" }).to_string(), behavior.latency);
+        }
+        let status = behavior.fail_status;
+        return (status, json!({ "code": status }).to_string(), behavior.latency);
+    }
+    if behavior.malformed {
+        return (200, "{\"code\": 200, \"data\": ".to_string(), behavior.latency);
+    }
+
+    if path == "/cover.gif" {
+        return (200, COVER_GIF.to_string(), behavior.latency);
+    }
+
+    let mut latency = behavior.latency;
+    let find_book = |key: &str| query.get(key).and_then(|id| state.books.get(id)).cloned();
+    let limit = |len: usize| behavior.partial.unwrap_or(len).min(len);
+
+    let body = match path.as_str() {
+        "/api/search" => {
+            let keyword = query.get("key").cloned().unwrap_or_default();
+            let books: Vec<Value> = state.books
+                .values()
+                .filter(|b| b.book_name.contains(&keyword))
+                .map(|b| b.info_json())
+                .collect();
+            json!({
+                "code": 200,
+                "data": { "search_tabs": [{ "tab_type": 3, "data": [{ "book_data": books }] }] }
+            })
+        }
+        "/api/detail" => match find_book("book_id") {
+            Some(book) => json!({ "code": 200, "data": { "code": 0, "data": book.info_json() } }),
+            None => json!({ "code": 404, "data": null }),
+        },
+        "/api/book" => match find_book("book_id") {
+            Some(book) => {
+                let chapters = &book.chapters[..limit(book.chapters.len())];
+                let mut volumes: Vec<Vec<Value>> = Vec::new();
+                let mut last_volume: Option<&str> = None;
+                for chapter in chapters {
+                    if last_volume != Some(chapter.volume_name.as_str()) {
+                        volumes.push(Vec::new());
+                        last_volume = Some(chapter.volume_name.as_str());
+                    }
+                    volumes.last_mut().unwrap().push(json!({
+                        "itemId": chapter.chapter_id,
+                        "title": chapter.title,
+                        "volume_name": chapter.volume_name,
+                    }));
+                }
+                json!({
+                    "code": 200,
+                    "data": {
+                        "code": 0,
+                        "data": {
+                            "allItemIds": chapters.iter().map(|c| c.chapter_id.clone()).collect::<Vec<_>>(),
+                            "chapterListWithVolume": volumes,
+                        }
+                    }
+                })
+            }
+            None => json!({ "code": 404, "data": null }),
+        },
+        "/api/directory" => match find_book("fq_id") {
+            Some(book) => {
+                let lists: Vec<Value> = book.chapters[..limit(book.chapters.len())]
+                    .iter()
+                    .map(|c| json!({ "chapter_id": c.chapter_id, "title": c.title, "volume_name": c.volume_name }))
+                    .collect();
+                json!({ "code": 200, "data": { "lists": lists } })
+            }
+            None => json!({ "code": 404, "data": null }),
+        },
+        "/api/content" => {
+            let item_id = query.get("item_id").cloned().unwrap_or_default();
+            if let Some(slow) = behavior.slow_items.get(&item_id) {
+                latency = *slow;
+            }
+            if behavior.failing_items.contains(&item_id) {
+                return (500, json!({ "code": 500 }).to_string(), latency);
+            }
+            let chapter = state.books
+                .values()
+                .flat_map(|b| b.chapters.iter())
+                .find(|c| c.chapter_id == item_id)
+                .cloned();
+            match chapter {
+                Some(c) => {
+                    let content = if behavior.blank_items.contains(&c.chapter_id) { "  " } else { c.content.as_str() };
+                    json!({
+                        "code": 200,
+                        "data": { "chapter_id": c.chapter_id, "title": c.title, "content": content }
+                    })
+                }
+                None => json!({ "code": 404, "data": null }),
+            }
+        }
+        "/api/ios/content" => {
+            let device_id = query.get("device_id").cloned().unwrap_or_default();
+            if !state.devices.contains(&device_id) {
+                return (200, json!({ "code": 403, "data": null }).to_string(), behavior.latency);
+            }
+            let item_id = query.get("item_id").cloned().unwrap_or_default();
+            let content_behavior = state.behaviors.get("/api/content").cloned().unwrap_or_default();
+            if content_behavior.failing_items.contains(&item_id) {
+                return (500, json!({ "code": 500 }).to_string(), behavior.latency);
+            }
+            let chapter = state.books
+                .values()
+                .flat_map(|b| b.chapters.iter())
+                .find(|c| c.chapter_id == item_id)
+                .cloned();
+            match chapter {
+                Some(c) => {
+                    let content = if behavior.blank_items.contains(&c.chapter_id) { "  " } else { c.content.as_str() };
+                    json!({
+                        "code": 200,
+                        "data": { "chapter_id": c.chapter_id, "title": c.title, "content": content }
+                    })
+                }
+                None => json!({ "code": 404, "data": null }),
+            }
+        }
+        "/api/ios/register" | "/api/device/register" => {
+            let device_id = format!("mock-device-{}", state.devices.len() + 1);
+            state.devices.push(device_id.clone());
+            json!({ "code": 200, "data": { "device_id": device_id, "install_id": "mock-install" } })
+        }
+        "/api/device/status" => {
+            let device_id = query.get("device_id").cloned().unwrap_or_default();
+            let valid = state.devices.contains(&device_id);
+            json!({ "code": 200, "data": { "device_id": device_id, "valid": valid } })
+        }
+        "/api/device/pool" => json!({
+            "code": 200,
+            "data": { "total": 10, "available": 10 - state.devices.len() }
+        }),
+        "/api/manga/progress" => match find_book("book_id") {
+            Some(book) => json!({
+                "code": 200,
+                "data": { "book_id": book.book_id, "total": book.chapters.len(), "finished": 0, "status": "pending" }
+            }),
+            None => json!({ "code": 404, "data": null }),
+        },
+        "/api/raw_full" => match find_book("book_id") {
+            Some(book) => {
+                let chapters: Vec<Value> = book.chapters[..limit(book.chapters.len())]
+                    .iter()
+                    .map(|c| {
+                        let content = if behavior.blank_items.contains(&c.chapter_id) { "  " } else { c.content.as_str() };
+                        json!({ "chapter_id": c.chapter_id, "title": c.title, "content": content })
+                    })
+                    .collect();
+                json!({ "code": 200, "data": { "chapters": chapters } })
+            }
+            None => json!({ "code": 404, "data": null }),
+        },
+        _ => return (404, json!({ "code": 404 }).to_string(), behavior.latency),
+    };
+
+    (200, body.to_string(), latency)
+}
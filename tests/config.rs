@@ -0,0 +1,132 @@
+mod common;
+
+use std::path::Path;
+use std::process::Command;
+
+use common::temp_dir;
+use fqdl::config::{init_config_dir, load_app_config_from, resolve_config_path, CONFIG_ENV};
+use fqdl::validate::{validate_config_file, validate_config_str, validate_user_config};
+
+fn write_config(path: &Path, node: &str) {
+    std::fs::write(path, serde_json::json!({
+        "version": "test",
+        "updated_at": "",
+        "api_sources": [{ "base_url": node }],
+        "endpoints": {},
+        "config": {}
+    }).to_string()).unwrap();
+}
+
+/// 在临时目录中运行 `fqdl config`，`FQDL_CONFIG` 只设置在子进程上，不影响并行运行的其他测试
+fn run_config_command(dir: &Path, args: &[&str], env_config: Option<&Path>) -> std::process::Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_fqdl"));
+    command
+        .args(args)
+        .current_dir(dir)
+        .env_remove(CONFIG_ENV)
+        .env("XDG_STATE_HOME", dir)
+        .env("XDG_CONFIG_HOME", dir);
+    if let Some(path) = env_config {
+        command.env(CONFIG_ENV, path);
+    }
+    command.output().unwrap()
+}
+
+#[test]
+fn explicit_path_then_env_then_default_locations() {
+    let dir = temp_dir("config");
+    let explicit = dir.join("explicit.json");
+    let from_env = dir.join("env.json");
+    write_config(&explicit, "http://explicit");
+    write_config(&from_env, "http://env");
+
+    let config = load_app_config_from(Some(&explicit)).unwrap();
+    assert_eq!(config.api_sources[0].base_url, "http://explicit");
+    assert_eq!(config.config_path, explicit);
+
+    let output = run_config_command(&dir, &["--config", explicit.to_str().unwrap(), "config"], Some(&from_env));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("配置文件路径: {}", explicit.display())), "{}", stdout);
+
+    let output = run_config_command(&dir, &["config"], Some(&from_env));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(&format!("配置文件路径: {}", from_env.display())), "{}", stdout);
+
+    // 显式指定的文件不存在时报错，而不是退回到默认位置
+    assert!(resolve_config_path(Some(&dir.join("missing.json"))).is_err());
+    let output = run_config_command(&dir, &["config"], Some(&dir.join("missing.json")));
+    assert!(!output.status.success());
+}
+
+#[test]
+fn cli_config_flags_select_the_loaded_file() {
+    let dir = temp_dir("config-cli");
+    let path = dir.join("project.json");
+    write_config(&path, "http://project");
+
+    for args in [
+        vec!["--config", path.to_str().unwrap(), "config"],
+        vec!["config", "--config-file", path.to_str().unwrap()],
+    ] {
+        let output = run_config_command(&dir, &args, None);
+
+        assert!(output.status.success());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert!(stdout.contains(&format!("配置文件路径: {}", path.display())), "{}", stdout);
+        assert!(stdout.contains("API 节点数量: 1"), "{}", stdout);
+    }
+}
+
+#[test]
+fn validate_reports_every_problem_with_its_path() {
+    let issues = validate_config_str(r#"{
+        "version": "1",
+        "updated_at": "",
+        "api_sources": [
+            { "base_url": "not a url" },
+            { "base_url": "https://a.example.com" },
+            { "base_url": "https://a.example.com/", "request_rate_limit": -1 }
+        ],
+        "endpoints": { "search": "/api/search", "detail": "api/detail" },
+        "config": { "max_workers": 0, "request_timeout": 0, "retry_jitter": 1.5, "retry_backoff_max": 1e30 }
+    }"#);
+
+    let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+    assert_eq!(paths, [
+        "$.api_sources[0].base_url",
+        "$.api_sources[2].base_url",
+        "$.api_sources[2].base_url",
+        "$.api_sources[2].request_rate_limit",
+        "$.endpoints.detail",
+        "$.config.max_workers",
+        "$.config.retry_backoff_max",
+        "$.config.retry_jitter",
+        "$.config.request_timeout",
+    ]);
+    assert!(issues[2].message.contains("$.api_sources[1]"));
+
+    let syntax = validate_config_str("{ \"api_sources\": [ }");
+    assert_eq!(syntax.len(), 1);
+    assert!(syntax[0].message.contains("第 1 行"));
+}
+
+#[test]
+fn init_writes_a_valid_starter_config() {
+    let dir = temp_dir("config-init");
+
+    let written = init_config_dir(&dir, false).unwrap();
+    assert_eq!(written, [dir.join("fanqie.json"), dir.join("config.toml")]);
+    assert!(validate_config_file(dir.join("fanqie.json")).unwrap().is_empty());
+    assert!(validate_user_config(dir.join("config.toml")).unwrap().is_empty());
+
+    std::fs::write(dir.join("config.toml"), "format = \"pdf\"\nconcurrency = 0\n").unwrap();
+    assert!(init_config_dir(&dir, false).unwrap().is_empty());
+    let paths: Vec<String> = validate_user_config(dir.join("config.toml"))
+        .unwrap()
+        .into_iter()
+        .map(|i| i.path)
+        .collect();
+    assert_eq!(paths, ["format", "concurrency"]);
+
+    assert_eq!(init_config_dir(&dir, true).unwrap().len(), 2);
+}
@@ -0,0 +1,154 @@
+mod common;
+
+use serde_json::json;
+use std::collections::HashSet;
+
+use common::{download_options, temp_dir, test_config, Behavior, MockBook, MockServer};
+use fqdl::client::FanqieClient;
+use fqdl::device::DeviceIdentity;
+use fqdl::error::FanqieError;
+
+#[tokio::test]
+async fn ios_content_replaces_failing_content_endpoint() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("6001", 5));
+    server.set_behavior("/api/content", Behavior {
+        always_status: Some(503),
+        ..Default::default()
+    });
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({ "max_retries": 0 }))).unwrap();
+    let dir = temp_dir("ios-fallback");
+
+    let path = client.downloader(download_options("6001", &dir, "txt")).download_book().await.unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains("第5章第二段。"));
+    assert!(!text.contains("下载失败"));
+
+    // 所有章节共用一次注册得到的设备
+    assert_eq!(server.hits("/api/ios/register"), 1);
+    assert_eq!(server.hits("/api/ios/content"), 5);
+    let device = client.api().device().await.unwrap();
+    assert_eq!(server.devices(), [device.device_id.as_str()]);
+    assert!(server
+        .requests()
+        .iter()
+        .filter(|r| r.path == "/api/ios/content")
+        .all(|r| r.query.get("device_id") == Some(&device.device_id)));
+}
+
+#[tokio::test]
+async fn rejected_restored_device_is_registered_again() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("6003", 3));
+    server.set_behavior("/api/content", Behavior {
+        always_status: Some(503),
+        ..Default::default()
+    });
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({ "max_retries": 0 }))).unwrap();
+    client.api().restore_device(DeviceIdentity::new("revoked-device".to_string(), None)).await;
+    let dir = temp_dir("ios-revoked");
+
+    let path = client.downloader(download_options("6003", &dir, "txt")).download_book().await.unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(!text.contains("下载失败"));
+
+    // 失效的设备只重新注册一次，之后的章节都使用新设备
+    assert_eq!(server.hits("/api/ios/register"), 1);
+    let device = client.api().device().await.unwrap();
+    assert_eq!(server.devices(), [device.device_id.as_str()]);
+}
+
+#[tokio::test]
+async fn restored_device_skips_registration() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("6002", 2));
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+    let api = client.api();
+
+    let registered = api.register_device().await.unwrap().data.unwrap();
+    api.restore_device(DeviceIdentity::new(registered.device_id.clone(), None)).await;
+
+    assert_eq!(api.ensure_device().await.unwrap().device_id, registered.device_id);
+    assert_eq!(server.hits("/api/ios/register"), 0);
+
+    let content = api.get_ios_content("60020001", &registered.device_id).await.unwrap();
+    assert_eq!(content.data.unwrap().title, "第1章 测试章节");
+
+    let status = api.get_device_status(&registered.device_id).await.unwrap();
+    assert_eq!(status.data.unwrap().valid, Some(true));
+    let pool = api.get_device_pool().await.unwrap().data.unwrap();
+    assert_eq!((pool.total, pool.available), (Some(10), Some(9)));
+    let progress = api.get_manga_progress("6002").await.unwrap().data.unwrap();
+    assert_eq!(progress.total, Some(2));
+
+    let path = temp_dir("device").join("device.json");
+    let device = api.device().await.unwrap();
+    device.save(&path).unwrap();
+    assert_eq!(DeviceIdentity::load(&path).unwrap(), device);
+}
+
+#[tokio::test]
+async fn throttled_or_locked_chapters_do_not_use_ios_content() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("6004", 2));
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({ "max_retries": 0, "throttle_cooldown": 0 }))).unwrap();
+
+    for status in [402, 404, 429] {
+        server.set_behavior("/api/content", Behavior {
+            always_status: Some(status),
+            ..Default::default()
+        });
+        assert!(client.downloader(download_options("6004", temp_dir("ios-skip"), "txt")).download_chapter("60040001").await.is_err());
+    }
+
+    assert_eq!(server.hits("/api/ios/register"), 0);
+    assert_eq!(server.hits("/api/ios/content"), 0);
+}
+
+#[tokio::test]
+async fn blank_ios_content_keeps_the_device() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("6005", 2));
+    server.set_behavior("/api/content", Behavior {
+        blank_items: HashSet::from(["60050001".to_string()]),
+        ..Default::default()
+    });
+    server.set_behavior("/api/ios/content", Behavior {
+        blank_items: HashSet::from(["60050001".to_string()]),
+        ..Default::default()
+    });
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({ "max_retries": 0 }))).unwrap();
+    let downloader = client.downloader(download_options("6005", temp_dir("ios-blank"), "txt"));
+
+    // 正文为空时改用 iOS 接口，iOS 接口同样为空不代表设备失效
+    for _ in 0..2 {
+        assert!(matches!(downloader.download_chapter("60050001").await, Err(FanqieError::ChapterFetch(_))));
+    }
+    assert_eq!(server.hits("/api/ios/content"), 2);
+    assert_eq!(server.hits("/api/ios/register"), 1);
+}
+
+#[tokio::test]
+async fn device_invalid_code_triggers_registration() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("6006", 1));
+    server.set_behavior("/api/content", Behavior {
+        always_status: Some(503),
+        ..Default::default()
+    });
+    server.set_behavior("/api/ios/content", Behavior {
+        fail_first: 1,
+        fail_code: Some(4031),
+        ..Default::default()
+    });
+    let client = FanqieClient::from_config(test_config(
+        &[&server.base_url],
+        json!({ "max_retries": 0, "device_invalid_codes": [4031] }),
+    ))
+    .unwrap();
+    let downloader = client.downloader(download_options("6006", temp_dir("ios-invalid"), "txt"));
+
+    let content = downloader.download_chapter("60060001").await.unwrap();
+    assert_eq!(content.title, "第1章 测试章节");
+    assert_eq!(server.hits("/api/ios/register"), 2);
+}
@@ -0,0 +1,143 @@
+mod common;
+
+use serde_json::json;
+use std::collections::HashSet;
+
+use common::{download_options, temp_dir, test_config, Behavior, MockBook, MockServer};
+use fqdl::cache::ChapterCache;
+use fqdl::client::FanqieClient;
+use fqdl::downloader::Downloader;
+use fqdl::error::FanqieError;
+
+fn setup(book: MockBook) -> (MockServer, FanqieClient) {
+    let server = MockServer::start();
+    server.add_book(book);
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({ "max_retries": 0 }))).unwrap();
+    (server, client)
+}
+
+fn chapter_titles(text: &str) -> Vec<&str> {
+    text.lines().filter(|line| line.starts_with('第') && line.contains("测试章节")).collect()
+}
+
+#[tokio::test]
+async fn downloads_selected_range_in_order() {
+    let (_server, client) = setup(MockBook::sample("3001", 8));
+    let dir = temp_dir("range");
+
+    let mut range = download_options("3001", &dir, "txt");
+    range.start_chapter = Some(3);
+    range.end_chapter = Some(5);
+
+    let path = client.downloader(range).download_book().await.unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        chapter_titles(&text),
+        vec!["第3章 测试章节", "第4章 测试章节", "第5章 测试章节"]
+    );
+}
+
+#[tokio::test]
+async fn reversed_or_out_of_bounds_ranges_are_rejected() {
+    let (_server, client) = setup(MockBook::sample("3006", 8));
+    let dir = temp_dir("bad-range");
+
+    for full_download in [false, true] {
+        let mut reversed = download_options("3006", &dir, "txt");
+        reversed.start_chapter = Some(6);
+        reversed.end_chapter = Some(3);
+        reversed.full_download = full_download;
+        let result = client.downloader(reversed).download_book().await;
+        assert!(matches!(result, Err(FanqieError::Download(_))), "{:?}", result);
+    }
+
+    let downloader = client.downloader(download_options("3006", &dir, "txt"));
+    let chapters = downloader.get_chapters().await.unwrap();
+    assert!(matches!(downloader.download_full(&chapters, 4..20).await, Err(FanqieError::Download(_))));
+    let reversed = std::ops::Range { start: 5, end: 2 };
+    assert!(matches!(downloader.download_full(&chapters, reversed).await, Err(FanqieError::Download(_))));
+}
+
+#[tokio::test]
+async fn full_download_falls_back_for_chapters_missing_from_raw_full() {
+    let (server, client) = setup(MockBook::sample("3002", 8));
+    server.set_behavior("/api/raw_full", Behavior {
+        partial: Some(4),
+        ..Default::default()
+    });
+    let dir = temp_dir("full");
+
+    let mut full = download_options("3002", &dir, "txt");
+    full.start_chapter = Some(3);
+    full.end_chapter = Some(6);
+    full.full_download = true;
+
+    let path = client.downloader(full).download_book().await.unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(server.hits("/api/raw_full"), 1);
+    assert_eq!(server.hits("/api/content"), 2);
+    assert_eq!(
+        chapter_titles(&text),
+        vec!["第3章 测试章节", "第4章 测试章节", "第5章 测试章节", "第6章 测试章节"]
+    );
+}
+
+#[tokio::test]
+async fn blank_raw_full_chapters_are_retried_and_results_cached() {
+    let (server, client) = setup(MockBook::sample("3005", 4));
+    server.set_behavior("/api/raw_full", Behavior {
+        blank_items: HashSet::from(["30050002".to_string()]),
+        ..Default::default()
+    });
+    server.set_behavior("/api/content", Behavior {
+        failing_items: HashSet::from(["30050002".to_string()]),
+        ..Default::default()
+    });
+    let dir = temp_dir("full-blank");
+    let cache_root = temp_dir("full-blank-cache");
+
+    let mut full = download_options("3005", &dir, "txt");
+    full.full_download = true;
+    full.use_cache = true;
+
+    // 空白章节不算下载成功，逐章重试仍失败时报告缺失；其余章节已写入缓存
+    let result = Downloader::with_cache_root(client.api(), full.clone(), &cache_root).download_book().await;
+    assert!(matches!(result, Err(FanqieError::IncompleteDownload(1))));
+    let cache = ChapterCache::new(&cache_root, "3005");
+    assert!(cache.contains("30050001"));
+    assert!(!cache.contains("30050002"));
+
+    // 整本接口不可用时从缓存继续，只下载缺少的章节
+    server.set_behavior("/api/raw_full", Behavior {
+        always_status: Some(500),
+        ..Default::default()
+    });
+    server.set_behavior("/api/content", Behavior::default());
+    let content_hits = server.hits("/api/content");
+    let path = Downloader::with_cache_root(client.api(), full, &cache_root).download_book().await.unwrap();
+    assert_eq!(server.hits("/api/content") - content_hits, 1);
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains("第2章第一段。"));
+}
+
+#[tokio::test]
+async fn missing_chapters_keep_their_position() {
+    let (server, client) = setup(MockBook::sample("3003", 5));
+    server.set_behavior("/api/content", Behavior {
+        failing_items: HashSet::from(["30030002".to_string()]),
+        ..Default::default()
+    });
+    let dir = temp_dir("missing");
+
+    let result = client.downloader(download_options("3003", &dir, "txt")).download_book().await;
+    assert!(matches!(result, Err(FanqieError::IncompleteDownload(1))));
+
+    let mut partial = download_options("3003", &dir, "txt");
+    partial.allow_partial = true;
+    let path = client.downloader(partial).download_book().await.unwrap();
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(chapter_titles(&text).len(), 5);
+    let placeholder = text.find("本章下载失败").unwrap();
+    assert!(text.find("第2章 测试章节").unwrap() < placeholder);
+    assert!(placeholder < text.find("第3章 测试章节").unwrap());
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use serde_json::json;
+
+use common::{test_config, Behavior, MockBook, MockServer};
+use fqdl::api::ApiErrorKind;
+use fqdl::client::FanqieClient;
+use fqdl::error::FanqieError;
+
+fn nodes() -> (MockServer, MockServer, FanqieClient) {
+    let first = MockServer::start();
+    let second = MockServer::start();
+    for server in [&first, &second] {
+        server.add_book(MockBook::sample("8001", 2));
+    }
+    let client = FanqieClient::from_config(test_config(
+        &[&first.base_url, &second.base_url],
+        json!({ "max_retries": 2 }),
+    ))
+    .unwrap();
+    (first, second, client)
+}
+
+#[tokio::test]
+async fn unknown_inner_error_code_fails_over_and_marks_node() {
+    let (first, second, client) = nodes();
+    first.set_behavior("/api/detail", Behavior {
+        fail_first: usize::MAX,
+        fail_body: Some(json!({ "code": 200, "data": { "code": 110, "message": "参数错误", "data": null } })),
+        ..Default::default()
+    });
+
+    let info = client.book_info("8001").await.unwrap();
+    assert_eq!(info.book_name, "测试书籍8001");
+    assert_eq!(first.hits("/api/detail"), 1);
+    assert_eq!(second.hits("/api/detail"), 1);
+
+    // 未知错误码计入节点健康度
+    let health = client.api().node_health();
+    let stats = health.get(&first.base_url).unwrap();
+    assert_eq!(stats.consecutive_failures, 1);
+    assert!(stats.last_error.as_deref().is_some_and(|e| e.contains("110")), "{:?}", stats.last_error);
+}
+
+#[tokio::test]
+async fn server_error_code_is_retried_on_the_same_node() {
+    let (first, second, client) = nodes();
+    first.set_behavior("/api/detail", Behavior {
+        fail_first: 1,
+        fail_body: Some(json!({ "code": 500, "message": "internal error" })),
+        ..Default::default()
+    });
+
+    client.book_info("8001").await.unwrap();
+    assert_eq!(first.hits("/api/detail"), 2);
+    assert_eq!(second.hits("/api/detail"), 0);
+}
+
+#[tokio::test]
+async fn missing_book_and_locked_chapter_map_to_specific_errors() {
+    let (first, second, client) = nodes();
+
+    let missing = client.book_info("9999").await;
+    assert!(matches!(&missing, Err(FanqieError::BookNotFound(id)) if id == "9999"), "{:?}", missing.err());
+    assert_eq!(first.hits("/api/detail") + second.hits("/api/detail"), 2);
+
+    for server in [&first, &second] {
+        server.set_behavior("/api/content", Behavior {
+            fail_first: usize::MAX,
+            fail_body: Some(json!({ "code": 403, "message": "该章节需要购买", "data": null })),
+            ..Default::default()
+        });
+    }
+    let locked = client.api().get_chapter_content("80010001").await;
+    assert!(matches!(&locked, Err(FanqieError::Locked(msg)) if msg.contains("80010001")), "{:?}", locked.err());
+}
+
+#[tokio::test]
+async fn forbidden_code_maps_to_forbidden_error() {
+    let (first, second, client) = nodes();
+    for server in [&first, &second] {
+        server.set_behavior("/api/detail", Behavior {
+            fail_first: usize::MAX,
+            fail_status: 403,
+            ..Default::default()
+        });
+    }
+
+    let result = client.book_info("8001").await;
+    assert!(matches!(&result, Err(FanqieError::Forbidden(msg)) if msg.contains("403")), "{:?}", result.err());
+}
+
+#[test]
+fn classifies_codes_and_messages() {
+    let throttle_codes = [429, 1002];
+    let classify = |code, message| ApiErrorKind::classify(code, message, &throttle_codes);
+
+    assert_eq!(classify(1002, None), ApiErrorKind::Throttled);
+    assert_eq!(classify(400, Some("请求过于频繁")), ApiErrorKind::Throttled);
+    assert_eq!(classify(503, None), ApiErrorKind::Server);
+    assert_eq!(classify(402, None), ApiErrorKind::Locked);
+    assert_eq!(classify(1, Some("VIP 章节")), ApiErrorKind::Locked);
+    assert_eq!(classify(403, Some("该章节需要购买")), ApiErrorKind::Locked);
+    assert_eq!(classify(403, None), ApiErrorKind::Forbidden);
+    assert_eq!(classify(401, Some("token invalid")), ApiErrorKind::Forbidden);
+    assert_eq!(classify(404, None), ApiErrorKind::NotFound);
+    assert_eq!(classify(1, Some("书籍已下架")), ApiErrorKind::NotFound);
+    assert_eq!(classify(110, Some("参数错误")), ApiErrorKind::Other);
+}
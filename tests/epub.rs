@@ -0,0 +1,129 @@
+mod common;
+
+use serde_json::json;
+use std::io::Read;
+use std::path::Path;
+
+use common::{download_options, temp_dir, test_config, MockBook, MockServer, COVER_GIF};
+use fqdl::client::FanqieClient;
+use fqdl::error::FanqieError;
+use fqdl::export::read_epub_chapters;
+
+fn read_entry(epub: &Path, name: &str) -> Option<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(epub).unwrap()).unwrap();
+    let mut file = archive.by_name(name).ok()?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data).unwrap();
+    Some(data)
+}
+
+#[tokio::test]
+async fn epub_embeds_cover_and_book_metadata() {
+    let server = MockServer::start();
+    let mut book = MockBook::sample("9101", 3);
+    book.cover = Some(format!("{}/cover.gif", server.base_url));
+    server.add_book(book.clone());
+
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+    let dir = temp_dir("epub-metadata");
+    let path = client.downloader(download_options("9101", &dir, "epub")).download_book().await.unwrap();
+
+    assert_eq!(server.hits("/cover.gif"), 1);
+    assert_eq!(read_entry(&path, "OEBPS/cover.gif").unwrap(), COVER_GIF.as_bytes());
+
+    let opf = String::from_utf8(read_entry(&path, "OEBPS/content.opf").unwrap()).unwrap();
+    assert!(opf.contains(r#"<dc:identifier id="epub-id-1">fanqie:9101</dc:identifier>"#), "{}", opf);
+    assert!(!opf.contains("urn:uuid:"));
+    assert!(opf.contains(&book.abstract_text));
+    assert!(opf.contains(r#"<meta name="cover" content="#));
+    assert!(opf.contains(r#"<meta name="fanqie:status" content="连载中"/>"#));
+    let word_count: usize = book.chapters.iter().map(|c| c.content.chars().count()).sum();
+    assert!(opf.contains(&format!(r#"<meta name="fanqie:word_count" content="{}"/>"#, word_count)));
+    let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+    assert!(opf.contains(&format!(r#"<dc:date opf:event="download">{}</dc:date>"#, today)));
+
+    // mimetype 仍需是第一个且不压缩的文件
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    let mimetype = archive.by_index(0).unwrap();
+    assert_eq!(mimetype.name(), "mimetype");
+    assert_eq!(mimetype.compression(), zip::CompressionMethod::Stored);
+    drop(mimetype);
+
+    let chapters = read_epub_chapters(&path).unwrap();
+    assert_eq!(chapters.len(), 3);
+    assert_eq!(chapters[0].content, book.chapters[0].content);
+}
+
+#[tokio::test]
+async fn missing_cover_does_not_fail_export() {
+    let server = MockServer::start();
+    let mut book = MockBook::sample("9102", 2);
+    book.cover = Some(format!("{}/missing.jpg", server.base_url));
+    server.add_book(book);
+
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+    let dir = temp_dir("epub-no-cover");
+    let path = client.downloader(download_options("9102", &dir, "epub")).download_book().await.unwrap();
+
+    let opf = String::from_utf8(read_entry(&path, "OEBPS/content.opf").unwrap()).unwrap();
+    assert!(opf.contains("fanqie:9102"));
+    assert!(!opf.contains(r#"<meta name="cover""#));
+    assert_eq!(read_epub_chapters(&path).unwrap().len(), 2);
+}
+
+fn read_text(epub: &Path, name: &str) -> String {
+    String::from_utf8(read_entry(epub, name).unwrap_or_else(|| panic!("缺少 {}", name))).unwrap()
+}
+
+#[tokio::test]
+async fn epub_uses_theme_custom_css_and_front_pages() {
+    let server = MockServer::start();
+    let book = MockBook::sample("9103", 2);
+    server.add_book(book.clone());
+
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+    let dir = temp_dir("epub-theme");
+    let css_path = dir.join("custom.css");
+    std::fs::write(&css_path, ".custom-rule { color: #333; }").unwrap();
+
+    let mut download = download_options("9103", &dir, "epub");
+    download.epub_theme = Some("vertical".to_string());
+    download.epub_css = Some(css_path.to_string_lossy().to_string());
+    let path = client.downloader(download).download_book().await.unwrap();
+
+    let css = read_text(&path, "OEBPS/stylesheet.css");
+    assert!(css.contains("writing-mode: vertical-rl"));
+    assert!(css.find("writing-mode").unwrap() < css.find(".custom-rule").unwrap(), "自定义样式应在主题之后");
+
+    let chapter = read_text(&path, "OEBPS/chapter_0000.xhtml");
+    assert!(chapter.contains(r#"<link rel="stylesheet" type="text/css" href="stylesheet.css"/>"#));
+
+    let opf = read_text(&path, "OEBPS/content.opf");
+    assert!(opf.contains(r#"page-progression-direction="rtl""#));
+    assert!(opf.contains(r#"type="title-page""#));
+
+    let title = read_text(&path, "OEBPS/title.xhtml");
+    assert!(title.contains(&book.book_name) && title.contains(&book.author));
+    let about = read_text(&path, "OEBPS/about.xhtml");
+    assert!(about.contains("关于本书") && about.contains(&book.abstract_text) && about.contains("2 章"));
+    assert!(read_text(&path, "OEBPS/toc.ncx").contains("关于本书"));
+
+    // 扉页和信息页不计入章节
+    assert_eq!(read_epub_chapters(&path).unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn unknown_theme_fails_before_downloading_chapters() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("9104", 2));
+
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+    let dir = temp_dir("epub-bad-theme");
+    let mut download = download_options("9104", &dir, "epub");
+    download.epub_theme = Some("neon".to_string());
+
+    let result = client.downloader(download).download_book().await;
+
+    assert!(matches!(result, Err(FanqieError::EpubGeneration(ref message)) if message.contains("neon")), "{:?}", result);
+    assert_eq!(server.hits("/api/content"), 0);
+}
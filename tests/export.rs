@@ -0,0 +1,184 @@
+mod common;
+
+use std::collections::HashSet;
+
+use serde_json::json;
+
+use common::{download_options, temp_dir, test_config, Behavior, MockBook, MockServer};
+use fqdl::client::FanqieClient;
+use fqdl::error::FanqieError;
+use fqdl::export::read_epub_chapters;
+use fqdl::manifest::{content_hash, DownloadManifest};
+use fqdl::update::UpdateOptions;
+
+fn update_options(target: &std::path::Path) -> UpdateOptions {
+    UpdateOptions {
+        target: target.to_string_lossy().to_string(),
+        save_path: String::new(),
+        use_cache: false,
+        allow_partial: false,
+    }
+}
+
+#[tokio::test]
+async fn exports_and_updates_txt_and_epub() {
+    let server = MockServer::start();
+    let book = MockBook::sample("4001", 4);
+    server.add_book(book.clone());
+
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+
+    let dir = temp_dir("export");
+    let save_path = dir.to_str().unwrap();
+
+    // TXT 导出与清单
+    let txt_path = client.downloader(download_options("4001", save_path, "txt")).download_book().await.unwrap();
+    let text = std::fs::read_to_string(&txt_path).unwrap();
+    assert!(text.starts_with("书名: 测试书籍4001\n作者: 测试作者\n"));
+    for chapter in &book.chapters {
+        assert!(text.contains(&chapter.content));
+    }
+
+    let manifest = DownloadManifest::load(DownloadManifest::path_for(&txt_path)).unwrap();
+    assert_eq!(manifest.book_id, "4001");
+    assert_eq!(manifest.format, "txt");
+    assert_eq!(manifest.source_node, server.base_url);
+    assert_eq!(manifest.chapters.len(), 4);
+    assert_eq!(manifest.chapters[0].sha256, content_hash(&book.chapters[0].content));
+
+    // EPUB 导出后可以读回全部章节
+    let epub_path = client.downloader(download_options("4001", save_path, "epub")).download_book().await.unwrap();
+    let chapters = read_epub_chapters(&epub_path).unwrap();
+    assert_eq!(chapters.len(), 4);
+    assert_eq!(chapters[3].title, book.chapters[3].title);
+    assert_eq!(chapters[3].content, book.chapters[3].content);
+
+    // 连载更新只下载新章节
+    let longer = MockBook::sample("4001", 6);
+    server.add_book(longer.clone());
+    server.reset_hits();
+
+    let results = client.updater(update_options(&txt_path)).run().await.unwrap();
+    assert_eq!(results[0].new_chapters, 2);
+    assert_eq!(server.hits("/api/content"), 2);
+
+    let text = std::fs::read_to_string(&txt_path).unwrap();
+    assert!(text.contains(&longer.chapters[5].content));
+    let manifest = DownloadManifest::load(DownloadManifest::path_for(&txt_path)).unwrap();
+    assert_eq!(manifest.last_chapter_id(), Some(longer.chapters[5].chapter_id.as_str()));
+
+    let results = client.updater(update_options(&epub_path)).run().await.unwrap();
+    assert_eq!(results[0].new_chapters, 2);
+    let chapters = read_epub_chapters(&results[0].output_path).unwrap();
+    assert_eq!(chapters.len(), 6);
+    assert_eq!(chapters[5].content, longer.chapters[5].content);
+
+    let results = client.updater(update_options(&txt_path)).run().await.unwrap();
+    assert_eq!(results[0].new_chapters, 0);
+}
+
+#[tokio::test]
+async fn update_refetches_trailing_missing_chapters() {
+    let server = MockServer::start();
+    let book = MockBook::sample("4005", 4);
+    server.add_book(book.clone());
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+    let dir = temp_dir("export-trailing-missing");
+
+    for format in ["txt", "md", "epub"] {
+        // 部分下载时最后一章写入占位内容并在清单中标记为缺失
+        server.set_behavior("/api/content", Behavior {
+            failing_items: HashSet::from(["40050004".to_string()]),
+            ..Default::default()
+        });
+        let mut partial = download_options("4005", &dir, format);
+        partial.allow_partial = true;
+        let path = client.downloader(partial).download_book().await.unwrap();
+        let manifest = DownloadManifest::load(DownloadManifest::path_for(&path)).unwrap();
+        assert!(manifest.chapters[3].missing);
+
+        // 更新时重新下载缺失的章节并替换占位内容
+        server.set_behavior("/api/content", Behavior::default());
+        let results = client.updater(update_options(&path)).run().await.unwrap();
+        assert_eq!(results[0].new_chapters, 1, "{}", format);
+
+        let contents: Vec<String> = if format == "epub" {
+            read_epub_chapters(&path).unwrap().into_iter().map(|c| c.content).collect()
+        } else {
+            vec![std::fs::read_to_string(&path).unwrap()]
+        };
+        let text = contents.concat();
+        assert!(text.contains("第4章第一段"), "{}", format);
+        assert!(!text.contains("本章下载失败"), "{}", format);
+        assert_eq!(text.matches("第3章第一段").count(), 1, "{}", format);
+        if format == "epub" {
+            assert_eq!(contents.len(), 4);
+        }
+
+        let manifest = DownloadManifest::load(DownloadManifest::path_for(&path)).unwrap();
+        assert_eq!(manifest.chapters.len(), 4);
+        assert!(manifest.chapters.iter().all(|c| !c.missing), "{}", format);
+    }
+}
+
+#[test]
+fn epub_chapters_are_read_in_numeric_order() {
+    let path = temp_dir("epub-order").join("order.epub");
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(&path).unwrap());
+    // 超过 9999 章后文件名位数变长，按字符串排序 chapter_10000 会排在 chapter_1001 之前
+    for index in [10000, 2, 1001, 9999] {
+        zip.start_file(format!("OEBPS/chapter_{:04}.xhtml", index), zip::write::FileOptions::default()).unwrap();
+        let html = format!("<body>\n    <h1>第{}章</h1>\n    <p>正文{}</p>\n</body>", index, index);
+        std::io::Write::write_all(&mut zip, html.as_bytes()).unwrap();
+    }
+    zip.start_file("OEBPS/chapter_notes.xhtml", zip::write::FileOptions::default()).unwrap();
+    zip.finish().unwrap();
+
+    let chapters = read_epub_chapters(&path).unwrap();
+    let titles: Vec<&str> = chapters.iter().map(|c| c.title.as_str()).collect();
+    assert_eq!(titles, ["第2章", "第1001章", "第9999章", "第10000章"]);
+    assert_eq!(chapters[3].content, "正文10000");
+}
+
+#[tokio::test]
+async fn update_rejects_epub_that_does_not_match_manifest() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("4002", 4));
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+    let dir = temp_dir("export-mismatch");
+
+    let epub_path = client.downloader(download_options("4002", &dir, "epub")).download_book().await.unwrap();
+    // 清单少记录一章，EPUB 中的章节无法与清单一一对应
+    let mut manifest = DownloadManifest::load(DownloadManifest::path_for(&epub_path)).unwrap();
+    manifest.chapters.pop();
+    manifest.save(&epub_path).unwrap();
+    server.add_book(MockBook::sample("4002", 6));
+
+    let result = client.updater(update_options(&epub_path)).run().await;
+
+    assert!(matches!(result, Err(FanqieError::Download(ref message)) if message.contains("4 章")), "{:?}", result.err());
+    assert_eq!(read_epub_chapters(&epub_path).unwrap().len(), 4);
+}
+
+#[tokio::test]
+async fn update_rebuilds_epub_in_place_when_title_changes() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("4003", 2));
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+    let dir = temp_dir("export-renamed");
+
+    let epub_path = client.downloader(download_options("4003", &dir, "epub")).download_book().await.unwrap();
+    let mut renamed = MockBook::sample("4003", 3);
+    renamed.book_name = "改名后的书".to_string();
+    server.add_book(renamed);
+
+    let results = client.updater(update_options(&epub_path)).run().await.unwrap();
+
+    // 原文件原地更新，不留下旧书名的文件
+    assert_eq!(results[0].output_path, epub_path);
+    assert_eq!(read_epub_chapters(&epub_path).unwrap().len(), 3);
+    assert!(!dir.join("改名后的书.epub").exists());
+    let manifest = DownloadManifest::load(DownloadManifest::path_for(&epub_path)).unwrap();
+    assert_eq!(manifest.book.book_name, "改名后的书");
+    assert_eq!(manifest.chapters.len(), 3);
+}
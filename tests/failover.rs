@@ -0,0 +1,58 @@
+mod common;
+
+use serde_json::json;
+
+use common::{dead_node, test_config, Behavior, MockBook, MockServer};
+use fqdl::client::FanqieClient;
+
+#[tokio::test]
+async fn fails_over_past_dead_and_broken_nodes() {
+    let broken = MockServer::start();
+    broken.set_behavior("/api/detail", Behavior {
+        always_status: Some(502),
+        ..Default::default()
+    });
+
+    let healthy = MockServer::start();
+    healthy.add_book(MockBook::sample("1001", 3));
+
+    let dead = dead_node();
+    let client = FanqieClient::from_config(test_config(
+        &[&dead, &broken.base_url, &healthy.base_url],
+        json!({ "max_retries": 1 }),
+    ))
+    .unwrap();
+
+    let api = client.api();
+    let response = api.get_book_detail("1001").await.unwrap();
+
+    assert_eq!(response.code, 200);
+    assert_eq!(response.data.unwrap().data.unwrap().book_name, "测试书籍1001");
+    // 5xx 会在同一节点重试 max_retries 次后才切换节点
+    assert_eq!(broken.hits("/api/detail"), 2);
+    assert_eq!(healthy.hits("/api/detail"), 1);
+
+    assert_eq!(api.get_current_node().await, healthy.base_url);
+    assert_eq!(client.config().read().await.current_node_index, 2);
+}
+
+#[tokio::test]
+async fn clients_with_different_nodes_are_independent() {
+    let first = MockServer::start();
+    first.add_book(MockBook::sample("1002", 2));
+    let second = MockServer::start();
+    second.add_book(MockBook::sample("1002", 2));
+
+    let client_a = FanqieClient::from_config(test_config(&[&first.base_url], json!({}))).unwrap();
+    let client_b = FanqieClient::builder()
+        .config(test_config(&[&first.base_url], json!({})))
+        .nodes([second.base_url.clone()])
+        .build()
+        .unwrap();
+
+    client_a.book_info("1002").await.unwrap();
+    client_b.book_info("1002").await.unwrap();
+
+    assert_eq!(first.hits("/api/detail"), 1);
+    assert_eq!(second.hits("/api/detail"), 1);
+}
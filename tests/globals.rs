@@ -0,0 +1,29 @@
+mod common;
+
+use serde_json::json;
+
+use common::{test_config, MockBook, MockServer};
+use fqdl::api::{get_api_client, init_api_client};
+use fqdl::config::set_config;
+use fqdl::search::get_book_info;
+
+#[tokio::test]
+async fn global_client_can_be_reinitialized() {
+    assert!(get_api_client().is_err());
+    assert!(get_book_info("5001").await.is_err());
+
+    let first = MockServer::start();
+    first.add_book(MockBook::sample("5001", 1));
+    set_config(test_config(&[&first.base_url], json!({})));
+    init_api_client().await.unwrap();
+    get_book_info("5001").await.unwrap();
+
+    let second = MockServer::start();
+    second.add_book(MockBook::sample("5001", 1));
+    set_config(test_config(&[&second.base_url], json!({})));
+    init_api_client().await.unwrap();
+    get_book_info("5001").await.unwrap();
+
+    assert_eq!(first.hits("/api/detail"), 1);
+    assert_eq!(second.hits("/api/detail"), 1);
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+
+use common::{dead_node, test_config, MockBook, MockServer};
+use fqdl::api::BookDetailResponse;
+use fqdl::client::FanqieClient;
+use fqdl::config::NodeAuth;
+use fqdl::validate::validate_config_str;
+
+fn book_server() -> MockServer {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("5001", 1));
+    server
+}
+
+#[tokio::test]
+async fn global_and_node_headers_with_auth() {
+    let bearer = book_server();
+    let query = book_server();
+
+    let mut config = test_config(
+        &[&bearer.base_url, &query.base_url],
+        json!({
+            "headers": { "Referer": "https://mirror.example/", "X-Client": "fqdl" },
+            "user_agents": ["fqdl-test/1.0"]
+        }),
+    );
+    config.api_sources[0].headers = HashMap::from([
+        ("X-Client".to_string(), "node-one".to_string()),
+        ("X-Api-Key".to_string(), "secret-key".to_string()),
+    ]);
+    config.api_sources[0].auth = Some(NodeAuth::Bearer { token: "xyz".to_string() });
+    config.api_sources[1].auth = Some(NodeAuth::Query {
+        param: "token".to_string(),
+        token: "abc123".to_string(),
+    });
+
+    let client = FanqieClient::from_config(config).unwrap();
+    let api = client.api();
+    let params = HashMap::from([("book_id", "5001")]);
+    for server in [&bearer, &query] {
+        let (response, _) = api
+            .probe::<BookDetailResponse>(&server.base_url, "/api/detail", &params)
+            .await
+            .unwrap();
+        assert_eq!(response.code, 200);
+    }
+
+    let first = &bearer.requests()[0];
+    assert_eq!(first.header("referer"), Some("https://mirror.example/"));
+    assert_eq!(first.header("x-client"), Some("node-one"));
+    assert_eq!(first.header("x-api-key"), Some("secret-key"));
+    assert_eq!(first.header("authorization"), Some("Bearer xyz"));
+    assert_eq!(first.header("user-agent"), Some("fqdl-test/1.0"));
+    assert!(!first.query.contains_key("token"));
+
+    let second = &query.requests()[0];
+    assert_eq!(second.header("x-client"), Some("fqdl"));
+    assert_eq!(second.header("x-api-key"), None);
+    assert_eq!(second.header("authorization"), None);
+    assert_eq!(second.query.get("token").map(String::as_str), Some("abc123"));
+    assert_eq!(second.query.get("book_id").map(String::as_str), Some("5001"));
+}
+
+#[tokio::test]
+async fn query_token_is_not_recorded_in_node_errors() {
+    let unreachable = dead_node();
+    let healthy = book_server();
+
+    let mut config = test_config(&[&unreachable, &healthy.base_url], json!({ "max_retries": 0 }));
+    config.api_sources[0].auth = Some(NodeAuth::Query {
+        param: "token".to_string(),
+        token: "leaky-secret".to_string(),
+    });
+
+    let client = FanqieClient::from_config(config).unwrap();
+    assert_eq!(client.api().get_book_detail("5001").await.unwrap().code, 200);
+
+    let health = client.api().node_health();
+    let error = health.get(&unreachable).unwrap().last_error.clone().unwrap();
+    assert!(error.contains(&unreachable), "{}", error);
+    assert!(!error.contains("leaky-secret"), "{}", error);
+    assert!(!serde_json::to_string(&health).unwrap().contains("leaky-secret"));
+}
+
+#[tokio::test]
+async fn user_agent_rotates_per_request() {
+    let server = book_server();
+    let pool = ["agent-a", "agent-b", "agent-c"];
+
+    let client = FanqieClient::from_config(test_config(
+        &[&server.base_url],
+        json!({ "user_agents": pool, "user_agent_rotation": "per_request" }),
+    ))
+    .unwrap();
+
+    for _ in 0..30 {
+        client.api().get_book_detail("5001").await.unwrap();
+    }
+
+    let seen: HashSet<String> = server
+        .requests()
+        .iter()
+        .map(|r| r.header("user-agent").unwrap().to_string())
+        .collect();
+    assert!(seen.len() > 1, "User-Agent 未轮换: {:?}", seen);
+    assert!(seen.iter().all(|ua| pool.contains(&ua.as_str())));
+}
+
+#[test]
+fn invalid_header_name_is_rejected() {
+    let result = FanqieClient::from_config(test_config(
+        &["http://node.test"],
+        json!({ "headers": { "Bad Header": "value" } }),
+    ));
+    assert!(result.is_err());
+}
+
+#[test]
+fn validate_checks_headers_user_agents_and_auth() {
+    let issues = validate_config_str(r#"{
+        "version": "1",
+        "updated_at": "",
+        "api_sources": [
+            { "base_url": "https://a.example.com", "auth": { "type": "bearer" } },
+            { "base_url": "https://b.example.com", "headers": { "X-Key": 1 }, "auth": { "type": "query", "token": "t" } },
+            { "base_url": "https://c.example.com", "auth": { "type": "cookie" } }
+        ],
+        "endpoints": {},
+        "config": {
+            "headers": { "Bad Header": "v" },
+            "user_agents": ["ok", ""],
+            "user_agent_rotation": "random"
+        }
+    }"#);
+
+    let paths: Vec<&str> = issues.iter().map(|i| i.path.as_str()).collect();
+    assert_eq!(paths, [
+        "$.api_sources[0].auth.token",
+        "$.api_sources[1].headers.X-Key",
+        "$.api_sources[1].auth.param",
+        "$.api_sources[2].auth.type",
+        "$.config.headers.Bad Header",
+        "$.config.user_agents[1]",
+        "$.config.user_agent_rotation",
+    ]);
+}
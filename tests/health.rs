@@ -0,0 +1,107 @@
+mod common;
+
+use serde_json::json;
+
+use common::{temp_dir, test_config, Behavior, MockBook, MockServer};
+use fqdl::client::FanqieClient;
+use fqdl::error::FanqieError;
+use fqdl::health::{CircuitState, NodeHealth};
+
+#[tokio::test]
+async fn failing_node_is_opened_and_skipped() {
+    let broken = MockServer::start();
+    broken.set_behavior("/api/detail", Behavior {
+        always_status: Some(503),
+        ..Default::default()
+    });
+
+    let healthy = MockServer::start();
+    healthy.add_book(MockBook::sample("2001", 2));
+
+    let client = FanqieClient::from_config(test_config(
+        &[&broken.base_url, &healthy.base_url],
+        json!({ "max_retries": 3, "circuit_failure_threshold": 2, "circuit_cooldown": 60 }),
+    ))
+    .unwrap();
+    let api = client.api();
+
+    api.get_book_detail("2001").await.unwrap();
+    // 达到熔断阈值后不再继续重试
+    assert_eq!(broken.hits("/api/detail"), 2);
+    assert_eq!(api.node_health().state(&broken.base_url), CircuitState::Open);
+
+    for _ in 0..3 {
+        api.get_book_detail("2001").await.unwrap();
+    }
+    assert_eq!(broken.hits("/api/detail"), 2);
+    assert_eq!(healthy.hits("/api/detail"), 4);
+
+    let health = api.node_health();
+    let stats = health.get(&healthy.base_url).unwrap();
+    assert_eq!(stats.successes, 4);
+    assert!(stats.avg_latency_ms.is_some());
+}
+
+#[tokio::test]
+async fn requests_start_at_healthiest_node_and_health_persists() {
+    let flaky = MockServer::start();
+    flaky.add_book(MockBook::sample("2002", 1));
+    flaky.set_behavior("/api/detail", Behavior {
+        fail_first: 1,
+        fail_status: 500,
+        ..Default::default()
+    });
+
+    let fast = MockServer::start();
+    fast.add_book(MockBook::sample("2002", 1));
+
+    let config = test_config(&[&flaky.base_url, &fast.base_url], json!({ "max_retries": 0 }));
+    let client = FanqieClient::from_config(config.clone()).unwrap();
+
+    client.book_info("2002").await.unwrap();
+    client.book_info("2002").await.unwrap();
+    // 第一个节点失败过一次，之后的请求直接从健康的节点开始
+    assert_eq!(flaky.hits("/api/detail"), 1);
+    assert_eq!(fast.hits("/api/detail"), 2);
+
+    let path = temp_dir("health").join("node_health.json");
+    client.api().node_health().save(&path).unwrap();
+
+    let restored = FanqieClient::from_config(config).unwrap();
+    restored.api().restore_node_health(NodeHealth::load(&path).unwrap());
+    restored.book_info("2002").await.unwrap();
+
+    assert_eq!(flaky.hits("/api/detail"), 1);
+    assert_eq!(fast.hits("/api/detail"), 3);
+    assert_eq!(restored.api().node_health().get(&fast.base_url).unwrap().successes, 3);
+}
+
+#[tokio::test]
+async fn business_errors_do_not_open_the_breaker() {
+    let node = MockServer::start();
+    node.add_book(MockBook::sample("2003", 1));
+
+    let client = FanqieClient::from_config(test_config(
+        &[&node.base_url],
+        json!({ "max_retries": 1, "circuit_failure_threshold": 2, "circuit_cooldown": 60 }),
+    ))
+    .unwrap();
+    let api = client.api();
+
+    for _ in 0..3 {
+        let missing = client.book_info("9999").await;
+        assert!(matches!(missing, Err(FanqieError::BookNotFound(_))), "{:?}", missing.err());
+    }
+    assert_eq!(api.node_health().state(&node.base_url), CircuitState::Closed);
+    assert!(api.node_health().get(&node.base_url).is_none_or(|s| s.consecutive_failures == 0));
+
+    // 节点仍可正常重试
+    let before = node.hits("/api/detail");
+    node.set_behavior("/api/detail", Behavior {
+        fail_first: before + 1,
+        fail_status: 503,
+        ..Default::default()
+    });
+    api.get_book_detail("2003").await.unwrap();
+    assert_eq!(node.hits("/api/detail") - before, 2);
+}
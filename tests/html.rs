@@ -0,0 +1,75 @@
+mod common;
+
+use serde_json::json;
+
+use common::{download_options, temp_dir, test_config, MockBook, MockServer};
+use fqdl::client::FanqieClient;
+use fqdl::error::FanqieError;
+use fqdl::export::read_html_chapters;
+use fqdl::update::UpdateOptions;
+
+#[tokio::test]
+async fn exports_self_contained_html_and_updates_it() {
+    let server = MockServer::start();
+    let mut book = MockBook::sample("9301", 4);
+    book.chapters[0].content = "<b>不是标签</b> & 符号\n第二段".to_string();
+    server.add_book(book.clone());
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+
+    let dir = temp_dir("html-export");
+    let path = client.downloader(download_options("9301", &dir, "html")).download_book().await.unwrap();
+    assert_eq!(path.extension().unwrap(), "html");
+
+    let html = std::fs::read_to_string(&path).unwrap();
+    assert!(html.contains("<style>") && html.contains("<script>"));
+    assert!(!html.contains("<link") && !html.contains("src="), "不应引用外部资源");
+    assert!(html.contains(r#"id="theme-toggle""#) && html.contains(r#"id="font-larger""#));
+    assert!(html.contains(r##"<a href="#chapter-3">第3章 测试章节</a>"##));
+    assert!(html.contains(r#"<article class="chapter" id="chapter-3">"#));
+    assert!(html.contains("&lt;b&gt;不是标签&lt;/b&gt; &amp; 符号"));
+
+    // 目录按分卷嵌套
+    let toc = &html[html.find("<nav").unwrap()..html.find("</nav>").unwrap()];
+    let second_volume = toc.find("第二卷").unwrap();
+    assert!(toc.find("chapter-2").unwrap() < second_volume);
+    assert!(toc.find("chapter-3").unwrap() > second_volume);
+
+    let chapters = read_html_chapters(&path).unwrap();
+    assert_eq!(chapters.len(), 4);
+    assert_eq!(chapters[0].content, book.chapters[0].content);
+    assert_eq!(chapters[3].title, book.chapters[3].title);
+
+    // 更新时读回已有章节并重新生成
+    let mut longer = MockBook::sample("9301", 6);
+    longer.chapters[0] = book.chapters[0].clone();
+    server.add_book(longer.clone());
+    server.reset_hits();
+
+    let results = client.updater(UpdateOptions {
+        target: path.to_string_lossy().to_string(),
+        save_path: String::new(),
+        use_cache: false,
+        allow_partial: false,
+    }).run().await.unwrap();
+    assert_eq!(results[0].new_chapters, 2);
+    assert_eq!(server.hits("/api/content"), 2);
+
+    let chapters = read_html_chapters(&results[0].output_path).unwrap();
+    assert_eq!(chapters.len(), 6);
+    assert_eq!(chapters[0].content, book.chapters[0].content);
+    assert_eq!(chapters[5].content, longer.chapters[5].content);
+}
+
+#[tokio::test]
+async fn unknown_format_is_rejected_before_downloading() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("9302", 2));
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+
+    let dir = temp_dir("html-unknown-format");
+    let result = client.downloader(download_options("9302", &dir, "pdf")).download_book().await;
+
+    assert!(matches!(result, Err(FanqieError::UnsupportedFormat(ref message)) if message.contains("pdf")), "{:?}", result);
+    assert_eq!(server.hits("/api/content"), 0);
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use serde_json::json;
+
+use common::{download_options, temp_dir, test_config, MockBook, MockServer};
+use fqdl::client::FanqieClient;
+use fqdl::error::FanqieError;
+use fqdl::manifest::DownloadManifest;
+use fqdl::update::UpdateOptions;
+
+fn update_options(target: &std::path::Path) -> UpdateOptions {
+    UpdateOptions {
+        target: target.to_string_lossy().to_string(),
+        save_path: String::new(),
+        use_cache: false,
+        allow_partial: false,
+    }
+}
+
+#[tokio::test]
+async fn exports_and_appends_markdown() {
+    let server = MockServer::start();
+    let mut book = MockBook::sample("9401", 4);
+    book.chapters[0].content = "# 不是标题\n1. 不是列表".to_string();
+    server.add_book(book.clone());
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+
+    let dir = temp_dir("markdown-single");
+    let path = client.downloader(download_options("9401", &dir, "md")).download_book().await.unwrap();
+    assert_eq!(path.extension().unwrap(), "md");
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.starts_with("# 测试书籍9401\n\n作者: 测试作者"));
+    assert!(text.contains("> 这是一本用于测试的书。"));
+    assert!(text.contains("\n## 第一卷\n\n### 第1章 测试章节\n\n\\# 不是标题\n\n1\\. 不是列表\n"));
+    assert!(text.contains("\n### 第3章 测试章节\n\n第3章第一段。\n\n第3章第二段。\n"));
+
+    let longer = MockBook::sample("9401", 6);
+    server.add_book(longer);
+    let results = client.updater(update_options(&path)).run().await.unwrap();
+    assert_eq!(results[0].new_chapters, 2);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.ends_with("\n### 第6章 测试章节\n\n第6章第一段。\n\n第6章第二段。\n"));
+    assert_eq!(text.matches("\n## 第二卷\n").count(), 1);
+}
+
+#[tokio::test]
+async fn dir_layout_writes_one_file_per_chapter_and_index() {
+    let server = MockServer::start();
+    let book = MockBook::sample("9402", 3);
+    server.add_book(book.clone());
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+
+    let save = temp_dir("markdown-dir");
+    let mut options = download_options("9402", &save, "md");
+    options.layout = Some("dir".to_string());
+    let out = client.downloader(options).download_book().await.unwrap();
+    assert!(out.is_dir());
+    assert_eq!(out.file_name().unwrap(), "测试书籍9402");
+
+    let first = std::fs::read_to_string(out.join("0001-第1章 测试章节.md")).unwrap();
+    assert_eq!(first, "# 第1章 测试章节\n\n第1章第一段。\n\n第1章第二段。\n");
+    let index = std::fs::read_to_string(out.join("index.md")).unwrap();
+    assert!(index.contains("### 第一卷\n\n- [第1章 测试章节](<0001-第1章 测试章节.md>)\n"));
+    assert!(index.contains("### 第二卷\n\n- [第3章 测试章节](<0003-第3章 测试章节.md>)\n"));
+
+    let manifest = DownloadManifest::load(DownloadManifest::path_for(&out)).unwrap();
+    assert_eq!(manifest.format, "md");
+    assert_eq!(manifest.chapters.len(), 3);
+
+    // 更新只写入新章节的文件，并重新生成索引
+    std::fs::write(out.join("0001-第1章 测试章节.md"), "已修改").unwrap();
+    server.add_book(MockBook::sample("9402", 5));
+    let results = client.updater(update_options(&out)).run().await.unwrap();
+    assert_eq!(results[0].new_chapters, 2);
+    assert_eq!(results[0].output_path, out);
+
+    assert_eq!(std::fs::read_to_string(out.join("0001-第1章 测试章节.md")).unwrap(), "已修改");
+    assert!(out.join("0005-第5章 测试章节.md").is_file());
+    let index = std::fs::read_to_string(out.join("index.md")).unwrap();
+    assert!(index.contains("- [第1章 测试章节](<0001-第1章 测试章节.md>)"));
+    assert!(index.contains("- [第5章 测试章节](<0005-第5章 测试章节.md>)"));
+}
+
+#[tokio::test]
+async fn dir_layout_writes_txt_files() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("9403", 2));
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+
+    let save = temp_dir("txt-dir");
+    let mut options = download_options("9403", &save, "txt");
+    options.layout = Some("dir".to_string());
+    let out = client.downloader(options).download_book().await.unwrap();
+
+    let second = std::fs::read_to_string(out.join("0002-第2章 测试章节.txt")).unwrap();
+    assert_eq!(second, "第2章 测试章节\n\n第2章第一段。\n第2章第二段。\n");
+    let index = std::fs::read_to_string(out.join("index.txt")).unwrap();
+    assert!(index.starts_with("书名: 测试书籍9403\n作者: 测试作者\n"));
+    assert!(index.contains("\n第一卷\n0001-第1章 测试章节.txt\n"));
+}
+
+#[tokio::test]
+async fn dir_layout_rejects_single_file_formats() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("9404", 2));
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+
+    let save = temp_dir("epub-dir");
+    let mut options = download_options("9404", &save, "epub");
+    options.layout = Some("dir".to_string());
+    let result = client.downloader(options).download_book().await;
+
+    assert!(matches!(result, Err(FanqieError::UnsupportedFormat(_))), "{:?}", result);
+    assert_eq!(server.hits("/api/content"), 0);
+}
@@ -0,0 +1,45 @@
+mod common;
+
+use serde_json::json;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use common::{download_options, test_config, Behavior, MockBook, MockServer};
+use fqdl::client::FanqieClient;
+use fqdl::downloader::DownloadProgress;
+
+#[tokio::test]
+async fn pipeline_keeps_workers_busy_and_emits_in_order() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("3201", 12));
+    server.set_behavior("/api/content", Behavior {
+        latency: Duration::from_millis(100),
+        ..Default::default()
+    });
+
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({ "max_workers": 4 }))).unwrap();
+
+    let downloader = client.downloader(download_options("3201", "", "txt"));
+    let chapters = downloader.get_chapters().await.unwrap();
+    let (tx, mut rx) = mpsc::channel(64);
+
+    let started = Instant::now();
+    let downloaded = downloader.download_all_chapters(&chapters, Some(tx)).await.unwrap();
+    let elapsed = started.elapsed();
+
+    // 12 章、每章 100 毫秒、4 个并发: 约 3 轮，远少于逐章下载的 1.2 秒
+    assert!(elapsed < Duration::from_millis(900), "并发不足: {:?}", elapsed);
+    let titles: Vec<_> = downloaded.contents.iter().map(|c| c.title.clone()).collect();
+    let expected: Vec<_> = chapters.iter().map(|c| c.title.clone()).collect();
+    assert_eq!(titles, expected);
+
+    // 进度事件按章节顺序产出
+    let mut events = Vec::new();
+    while let Some(event) = rx.recv().await {
+        if let DownloadProgress::Chapter { current, title, .. } = event {
+            events.push((current, title));
+        }
+    }
+    let expected: Vec<_> = expected.into_iter().enumerate().map(|(i, title)| (i + 1, title)).collect();
+    assert_eq!(events, expected);
+}
@@ -0,0 +1,90 @@
+mod common;
+
+use serde_json::json;
+use std::time::Duration;
+
+use common::{dead_node, temp_dir, test_config, Behavior, MockBook, MockServer};
+use fqdl::client::FanqieClient;
+use fqdl::config::{load_config_from_file, save_api_source_order};
+use fqdl::probe::{EndpointKind, NodeProber, ProbeOptions, ProbeOutcome};
+
+#[tokio::test]
+async fn probes_each_endpoint_and_ranks_nodes() {
+    let slow = MockServer::start();
+    slow.add_book(MockBook::sample("3001", 2));
+    slow.set_behavior("/api/detail", Behavior {
+        latency: Duration::from_millis(150),
+        ..Default::default()
+    });
+
+    let fast = MockServer::start();
+    fast.add_book(MockBook::sample("3001", 2));
+
+    let no_content = MockServer::start();
+    no_content.add_book(MockBook::sample("3001", 2));
+    no_content.set_behavior("/api/content", Behavior {
+        always_status: Some(500),
+        ..Default::default()
+    });
+
+    let dead = dead_node();
+    let mut config = test_config(
+        &[&dead, &no_content.base_url, &slow.base_url, &fast.base_url],
+        json!({}),
+    );
+    config.api_sources[3].supports_full_download = false;
+    let client = FanqieClient::from_config(config).unwrap();
+
+    let probes = NodeProber::new(client.api(), ProbeOptions {
+        keyword: "测试".to_string(),
+        book_id: "3001".to_string(),
+    })
+    .run()
+    .await;
+
+    let order: Vec<&str> = probes.iter().map(|p| p.base_url.as_str()).collect();
+    assert_eq!(order, [
+        fast.base_url.as_str(),
+        slow.base_url.as_str(),
+        no_content.base_url.as_str(),
+        dead.as_str(),
+    ]);
+
+    assert!(matches!(probes[0].outcome(EndpointKind::RawFull), Some(ProbeOutcome::Skipped)));
+    assert_eq!(probes[1].success_count(), 4);
+    assert!(matches!(probes[2].outcome(EndpointKind::Content), Some(ProbeOutcome::Failed(_))));
+    assert_eq!(probes[3].success_count(), 0);
+
+    // 测速只发送一次请求，不重试
+    assert_eq!(no_content.hits("/api/content"), 1);
+    assert!(client.api().node_health().get(&dead).is_none());
+}
+
+#[test]
+fn saves_ranked_order_and_keeps_other_keys() {
+    let path = temp_dir("probe").join("fanqie.json");
+    std::fs::write(&path, r#"{
+  "version": "1",
+  "updated_at": "",
+  "api_sources": [
+    { "base_url": "http://a", "supports_full_download": true },
+    { "base_url": "http://b", "supports_full_download": false },
+    { "base_url": "http://c", "supports_full_download": true }
+  ],
+  "endpoints": { "ios_content": "/api/ios/content" },
+  "config": {}
+}"#).unwrap();
+
+    save_api_source_order(&path, &["http://c".to_string(), "http://a".to_string()]).unwrap();
+
+    let json = load_config_from_file(&path).unwrap();
+    let order: Vec<&str> = json.api_sources.iter().map(|s| s.base_url.as_str()).collect();
+    assert_eq!(order, ["http://c", "http://a", "http://b"]);
+    assert!(!json.api_sources[2].supports_full_download);
+
+    let raw = std::fs::read_to_string(&path).unwrap();
+    assert!(raw.contains("/api/ios/content"));
+    let value: serde_json::Value = serde_json::from_str(&raw).unwrap();
+    let keys: Vec<&str> = value.as_object().unwrap().keys().map(String::as_str).collect();
+    assert_eq!(keys, ["version", "updated_at", "api_sources", "endpoints", "config"]);
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use serde_json::json;
+use std::collections::HashMap;
+
+use common::{test_config, MockBook, MockServer};
+use fqdl::api::{ApiClient, BookDetailResponse};
+use fqdl::client::FanqieClient;
+
+async fn detail_via(api: &ApiClient, base_url: &str) -> BookDetailResponse {
+    let params = HashMap::from([("book_id", "4001")]);
+    let (response, _) = api.probe::<BookDetailResponse>(base_url, "/api/detail", &params).await.unwrap();
+    response
+}
+
+fn proxy_server() -> MockServer {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("4001", 1));
+    server
+}
+
+#[tokio::test]
+async fn global_proxy_with_authentication() {
+    let proxy = proxy_server();
+    let proxy_url = proxy.base_url.replace("http://", "http://reader:p%40ss@");
+
+    let client = FanqieClient::from_config(test_config(
+        &["http://fanqie-node.test"],
+        json!({ "proxy": proxy_url }),
+    ))
+    .unwrap();
+
+    let response = client.api().get_book_detail("4001").await.unwrap();
+    assert_eq!(response.code, 200);
+
+    let requests = proxy.proxied_requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(requests[0].host, "fanqie-node.test");
+    // base64("reader:p@ss")
+    assert_eq!(requests[0].proxy_authorization.as_deref(), Some("Basic cmVhZGVyOnBAc3M="));
+}
+
+#[tokio::test]
+async fn per_node_proxy_direct_and_no_proxy() {
+    let global = proxy_server();
+    let dedicated = proxy_server();
+    let direct = proxy_server();
+    let exempt = proxy_server();
+
+    let mut config = test_config(
+        &["http://node-one.test", &direct.base_url, "http://node-three.test", &exempt.base_url],
+        json!({ "proxy": global.base_url, "no_proxy": [exempt.base_url.trim_start_matches("http://").split(':').next().unwrap()] }),
+    );
+    config.api_sources[0].proxy = Some(dedicated.base_url.clone());
+    config.api_sources[1].proxy = Some("direct".to_string());
+    let client = FanqieClient::from_config(config).unwrap();
+    let api = client.api();
+
+    for base_url in ["http://node-one.test", direct.base_url.as_str(), "http://node-three.test", exempt.base_url.as_str()] {
+        assert_eq!(detail_via(&api, base_url).await.code, 200);
+    }
+
+    let hosts = |server: &MockServer| -> Vec<String> {
+        server.proxied_requests().into_iter().map(|r| r.host).collect()
+    };
+    assert_eq!(hosts(&dedicated), ["node-one.test"]);
+    assert_eq!(hosts(&global), ["node-three.test"]);
+    assert_eq!(direct.hits("/api/detail"), 1);
+    assert!(hosts(&direct).is_empty());
+    assert_eq!(exempt.hits("/api/detail"), 1);
+    assert!(hosts(&exempt).is_empty());
+}
+
+#[test]
+fn invalid_proxy_is_rejected() {
+    let result = FanqieClient::from_config(test_config(
+        &["http://node.test"],
+        json!({ "proxy": "not a proxy" }),
+    ));
+    assert!(result.is_err());
+}
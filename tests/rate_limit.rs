@@ -0,0 +1,92 @@
+mod common;
+
+use serde_json::{json, Value};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+use common::{temp_dir, MockBook, MockServer};
+use fqdl::config::CONFIG_ENV;
+
+/// 用 `api_sources` 和配置参数在独立目录中运行一次 `fqdl download`，返回模拟服务器收到的请求数和用时。
+/// 配置参数之外默认不限流，只有被测的设置生效
+fn run_download(name: &str, sources: Value, params: Value, extra_args: &[&str]) -> (usize, Duration) {
+    let node = MockServer::start();
+    node.add_book(MockBook::sample("1101", 6));
+
+    let dir = temp_dir(&format!("rate-{}", name));
+
+    let mut config = json!({
+        "max_retries": 0,
+        "request_timeout": 5,
+        "request_rate_limit": 0.0,
+        "api_rate_limit": 0
+    });
+    if let (Some(config), Some(params)) = (config.as_object_mut(), params.as_object()) {
+        for (key, value) in params {
+            config.insert(key.clone(), value.clone());
+        }
+    }
+    let mut sources = sources;
+    for source in sources.as_array_mut().unwrap() {
+        source["base_url"] = json!(node.base_url);
+    }
+    std::fs::write(dir.join("fanqie.json"), json!({
+        "version": "test",
+        "updated_at": "",
+        "api_sources": sources,
+        "endpoints": {},
+        "config": config
+    }).to_string()).unwrap();
+
+    let started = Instant::now();
+    let output = Command::new(env!("CARGO_BIN_EXE_fqdl"))
+        .args(extra_args)
+        .args(["download", "1101", "-p", dir.to_str().unwrap()])
+        .current_dir(&dir)
+        .env_remove(CONFIG_ENV)
+        .env("XDG_CONFIG_HOME", &dir)
+        .env("XDG_CACHE_HOME", &dir)
+        .env("XDG_STATE_HOME", &dir)
+        .output()
+        .unwrap();
+    let elapsed = started.elapsed();
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let requests = node.total_hits();
+    assert!(requests > 6, "请求数: {}", requests);
+    (requests, elapsed)
+}
+
+/// 令牌桶初始装满 `capacity` 个令牌，之后每秒补充 `rate` 个，发出 `requests` 个请求至少需要的时间
+fn minimum_elapsed(requests: usize, rate: f64, capacity: usize) -> Duration {
+    Duration::from_secs_f64(requests.saturating_sub(capacity) as f64 / rate * 0.9)
+}
+
+#[test]
+fn rate_flag_limits_all_requests() {
+    let (requests, elapsed) = run_download("cli", json!([{}]), json!({}), &["--rate", "4"]);
+    assert!(elapsed >= minimum_elapsed(requests, 4.0, 4), "--rate 未生效: {} 个请求用时 {:?}", requests, elapsed);
+}
+
+#[test]
+fn api_rate_limit_limits_all_requests() {
+    let (requests, elapsed) = run_download(
+        "global",
+        json!([{}]),
+        json!({ "api_rate_limit": 3, "rate_limit_window": 1.0 }),
+        &[],
+    );
+    assert!(elapsed >= minimum_elapsed(requests, 3.0, 3), "未限流: {} 个请求用时 {:?}", requests, elapsed);
+}
+
+#[test]
+fn request_rate_limit_spaces_requests_to_a_node() {
+    let (requests, elapsed) = run_download("node", json!([{}]), json!({ "request_rate_limit": 0.25 }), &[]);
+    assert!(elapsed >= minimum_elapsed(requests, 4.0, 1), "未限流: {} 个请求用时 {:?}", requests, elapsed);
+}
+
+#[test]
+fn node_setting_overrides_request_rate_limit() {
+    let (requests, elapsed) = run_download("node-override", json!([{ "request_rate_limit": 0.25 }]), json!({}), &[]);
+    assert!(elapsed >= minimum_elapsed(requests, 4.0, 1), "未限流: {} 个请求用时 {:?}", requests, elapsed);
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use serde_json::json;
+
+use common::{test_config, Behavior, MockBook, MockServer};
+use fqdl::client::FanqieClient;
+
+fn setup() -> (MockServer, MockServer, FanqieClient) {
+    let flaky = MockServer::start();
+    let healthy = MockServer::start();
+    for server in [&flaky, &healthy] {
+        server.add_book(MockBook::sample("2001", 3));
+    }
+
+    let client = FanqieClient::from_config(test_config(
+        &[&flaky.base_url, &healthy.base_url],
+        json!({ "max_retries": 2 }),
+    ))
+    .unwrap();
+
+    (flaky, healthy, client)
+}
+
+#[tokio::test]
+async fn retries_server_errors_on_the_same_node() {
+    let (flaky, healthy, client) = setup();
+    flaky.set_behavior("/api/detail", Behavior {
+        fail_first: 2,
+        fail_status: 503,
+        ..Default::default()
+    });
+
+    let response = client.api().get_book_detail("2001").await.unwrap();
+
+    assert_eq!(response.code, 200);
+    assert_eq!(flaky.hits("/api/detail"), 3);
+    assert_eq!(healthy.hits("/api/detail"), 0);
+}
+
+#[tokio::test]
+async fn does_not_retry_malformed_json() {
+    let (flaky, healthy, client) = setup();
+    flaky.set_behavior("/api/search", Behavior {
+        malformed: true,
+        ..Default::default()
+    });
+
+    let response = client.api().search_books("测试", 0).await.unwrap();
+
+    assert_eq!(response.code, 200);
+    assert_eq!(flaky.hits("/api/search"), 1);
+    assert_eq!(healthy.hits("/api/search"), 1);
+}
+
+#[tokio::test]
+async fn client_errors_fail_over_without_retry() {
+    let (flaky, healthy, client) = setup();
+    flaky.set_behavior("/api/directory", Behavior {
+        always_status: Some(404),
+        ..Default::default()
+    });
+
+    let response = client.api().get_directory("2001").await.unwrap();
+
+    assert_eq!(response.code, 200);
+    assert_eq!(flaky.hits("/api/directory"), 1);
+    assert_eq!(healthy.hits("/api/directory"), 1);
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use serde_json::json;
+use std::collections::HashMap;
+
+use common::{temp_dir, test_config};
+use fqdl::api::BookInfo;
+use fqdl::config::ConfigParams;
+use fqdl::export::render_file_name;
+use fqdl::settings::{SettingSource, Settings, UserConfig, DEFAULT_BATCH_SAVE_PATH};
+
+#[test]
+fn layers_user_config_env_and_cli_in_order() {
+    let dir = temp_dir("settings");
+    let path = dir.join("config.toml");
+    std::fs::write(&path, r#"
+save_path = "~/Books"
+format = "epub"
+concurrency = 8
+proxy = "http://127.0.0.1:7890"
+"#).unwrap();
+
+    let mut config = test_config(&["http://node"], json!({ "max_workers": 16 }));
+    let mut settings = Settings::from_app_config(&config);
+    assert_eq!(settings.concurrency.value, 16);
+    assert_eq!(settings.concurrency.source, SettingSource::ConfigFile);
+    let mut unset = config.clone();
+    unset.params.max_workers = None;
+    let unset = Settings::from_app_config(&unset);
+    assert_eq!(unset.concurrency.value, ConfigParams::default().workers());
+    assert_eq!(unset.concurrency.source, SettingSource::Default);
+    assert_eq!(unset.batch_save_path(), DEFAULT_BATCH_SAVE_PATH);
+
+    settings.merge_user_config(&UserConfig::load(&path).unwrap(), path.clone());
+    let env = HashMap::from([("FQDL_FORMAT", "txt"), ("FQDL_CONCURRENCY", "12")]);
+    settings.merge_env(|name| env.get(name).map(|v| v.to_string())).unwrap();
+    settings.concurrency.set(4, SettingSource::Cli);
+
+    assert_eq!(settings.save_path.value, "~/Books");
+    assert_eq!(settings.save_path.source, SettingSource::UserConfig);
+    assert_eq!(settings.batch_save_path(), "~/Books");
+    assert_eq!(settings.format.value, "txt");
+    assert_eq!(settings.format.source, SettingSource::Env("FQDL_FORMAT"));
+    assert_eq!(settings.concurrency.value, 4);
+    assert_eq!(settings.concurrency.source, SettingSource::Cli);
+    assert_eq!(settings.filename_template.source, SettingSource::Default);
+    assert_eq!(settings.user_config_path.as_deref(), Some(path.as_path()));
+
+    settings.apply_to(&mut config);
+    assert_eq!(config.params.max_workers, Some(4));
+    assert_eq!(config.params.proxy.as_deref(), Some("http://127.0.0.1:7890"));
+
+    let invalid = HashMap::from([("FQDL_CONCURRENCY", "many")]);
+    assert!(settings.merge_env(|name| invalid.get(name).map(|v| v.to_string())).is_err());
+}
+
+#[test]
+fn explicit_default_concurrency_is_reported_as_configured() {
+    let config = test_config(&["http://node"], json!({ "max_workers": 30 }));
+    let settings = Settings::from_app_config(&config);
+
+    assert_eq!(settings.concurrency.value, ConfigParams::default().workers());
+    assert_eq!(settings.concurrency.source, SettingSource::ConfigFile);
+}
+
+#[test]
+fn filename_template_placeholders() {
+    let book: BookInfo = serde_json::from_value(json!({
+        "book_id": "42",
+        "book_name": "书名: 副标题",
+        "author": "作者",
+    }))
+    .unwrap();
+
+    assert_eq!(render_file_name(&book, "{title}"), "书名_ 副标题");
+    assert_eq!(render_file_name(&book, "{author} - {title} [{book_id}]"), "作者 - 书名_ 副标题 [42]");
+    assert_eq!(render_file_name(&book, "  "), "书名_ 副标题");
+}
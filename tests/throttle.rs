@@ -0,0 +1,201 @@
+mod common;
+
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use common::{test_config, Behavior, MockBook, MockServer};
+use fqdl::api::BookDetailResponse;
+use fqdl::client::FanqieClient;
+use fqdl::error::FanqieError;
+use fqdl::utils::{parse_retry_after, TokenBucket};
+
+fn server() -> MockServer {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("7001", 2));
+    server
+}
+
+#[tokio::test]
+async fn waits_for_retry_after_before_retrying_the_node() {
+    let node = server();
+    node.set_behavior("/api/detail", Behavior {
+        fail_first: 1,
+        fail_status: 429,
+        retry_after: Some("1".to_string()),
+        ..Default::default()
+    });
+    let client = FanqieClient::from_config(test_config(&[&node.base_url], json!({}))).unwrap();
+
+    let started = Instant::now();
+    let response = client.api().get_book_detail("7001").await.unwrap();
+
+    assert_eq!(response.code, 200);
+    assert!(started.elapsed() >= Duration::from_millis(900), "未等待 Retry-After: {:?}", started.elapsed());
+    assert_eq!(node.hits("/api/detail"), 2);
+
+    let health = client.api().node_health();
+    let stats = health.get(&node.base_url).unwrap();
+    assert_eq!(stats.consecutive_failures, 0);
+    assert!(stats.throttled_until.is_none());
+}
+
+#[tokio::test]
+async fn throttle_code_pauses_node_and_fails_over() {
+    let throttled = server();
+    let healthy = server();
+    throttled.set_behavior("/api/detail", Behavior {
+        fail_first: usize::MAX,
+        fail_code: Some(429),
+        ..Default::default()
+    });
+    let client = FanqieClient::from_config(test_config(
+        &[&throttled.base_url, &healthy.base_url],
+        json!({ "throttle_cooldown": 120 }),
+    ))
+    .unwrap();
+
+    for _ in 0..3 {
+        assert_eq!(client.api().get_book_detail("7001").await.unwrap().code, 200);
+    }
+
+    // 暂停期间不再请求被限流的节点
+    assert_eq!(throttled.hits("/api/detail"), 1);
+    assert_eq!(healthy.hits("/api/detail"), 3);
+
+    let health = client.api().node_health();
+    let stats = health.get(&throttled.base_url).unwrap();
+    assert!(stats.last_error.as_deref().unwrap().contains("请求过于频繁"));
+    let remaining = health.throttle_remaining(&throttled.base_url).unwrap();
+    assert!(remaining > Duration::from_secs(100));
+}
+
+#[tokio::test]
+async fn persistent_throttling_surfaces_rate_limited() {
+    let node = server();
+    node.set_behavior("/api/detail", Behavior {
+        fail_first: usize::MAX,
+        fail_status: 429,
+        retry_after: Some("0".to_string()),
+        ..Default::default()
+    });
+    let client = FanqieClient::from_config(test_config(&[&node.base_url], json!({ "max_retries": 1 }))).unwrap();
+
+    let result = client.api().get_book_detail("7001").await;
+
+    assert!(matches!(result, Err(FanqieError::RateLimited(_))), "{:?}", result.err());
+    assert_eq!(node.hits("/api/detail"), 2);
+}
+
+#[tokio::test]
+async fn throttling_slows_other_nodes_without_rate_limit() {
+    let throttled = server();
+    let healthy = server();
+    throttled.set_behavior("/api/detail", Behavior {
+        fail_first: usize::MAX,
+        fail_status: 429,
+        ..Default::default()
+    });
+    // api_rate_limit 为 0 (与 --rate 0 相同): 平时不限流
+    let client = FanqieClient::from_config(test_config(
+        &[&throttled.base_url, &healthy.base_url],
+        json!({ "api_rate_limit": 0, "throttle_cooldown": 120 }),
+    ))
+    .unwrap();
+    let api = client.api();
+    let params = HashMap::from([("book_id", "7001")]);
+
+    let started = Instant::now();
+    for _ in 0..20 {
+        api.probe::<BookDetailResponse>(&healthy.base_url, "/api/detail", &params).await.unwrap();
+    }
+    assert!(started.elapsed() < Duration::from_millis(500), "不应限流: {:?}", started.elapsed());
+
+    // 第一个节点返回 429 后转到第二个节点，全局速率降为之前实际速率的一半
+    assert_eq!(api.get_book_detail("7001").await.unwrap().code, 200);
+    assert_eq!(throttled.hits("/api/detail"), 1);
+
+    let started = Instant::now();
+    for _ in 0..10 {
+        api.probe::<BookDetailResponse>(&healthy.base_url, "/api/detail", &params).await.unwrap();
+    }
+    assert!(started.elapsed() >= Duration::from_millis(600), "其他节点未降速: {:?}", started.elapsed());
+}
+
+#[tokio::test]
+async fn unlimited_limiter_slows_down_and_lifts_after_recovery() {
+    let bucket = TokenBucket::unlimited();
+    for _ in 0..8 {
+        assert!(bucket.try_acquire().await);
+    }
+    assert!(bucket.get_rate().is_infinite());
+
+    // 1 秒内 8 个请求: 降到每秒 4 个
+    bucket.slow_down().await;
+    assert_eq!(bucket.get_rate(), 4.0);
+    assert!(!bucket.try_acquire().await);
+
+    for _ in 0..100 {
+        bucket.recover();
+    }
+    assert!(bucket.get_rate().is_infinite());
+    assert!(bucket.try_acquire().await);
+}
+
+#[tokio::test]
+async fn shared_limiter_backs_off_and_recovers() {
+    let bucket = TokenBucket::new(16.0, 16.0);
+
+    bucket.slow_down().await;
+    bucket.slow_down().await;
+    assert_eq!(bucket.get_rate(), 4.0);
+    assert!(!bucket.try_acquire().await);
+
+    for _ in 0..100 {
+        bucket.recover();
+    }
+    assert_eq!(bucket.get_rate(), 16.0);
+
+    for _ in 0..10 {
+        bucket.slow_down().await;
+    }
+    assert_eq!(bucket.get_rate(), 1.0);
+}
+
+#[test]
+fn parses_retry_after_seconds_and_dates() {
+    assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+    assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), Some(Duration::ZERO));
+    let future = (chrono::Utc::now() + chrono::Duration::seconds(90)).to_rfc2822();
+    let parsed = parse_retry_after(&future).unwrap();
+    assert!(parsed > Duration::from_secs(85) && parsed <= Duration::from_secs(90));
+    assert_eq!(parse_retry_after("soon"), None);
+    assert_eq!(parse_retry_after("-5"), None);
+}
+
+#[test]
+fn huge_retry_after_does_not_panic() {
+    assert_eq!(parse_retry_after("1e20"), Some(Duration::MAX));
+    assert_eq!(parse_retry_after("99999999999999999999999"), Some(Duration::MAX));
+}
+
+#[tokio::test]
+async fn huge_retry_after_in_body_is_capped() {
+    let throttled = server();
+    let healthy = server();
+    throttled.set_behavior("/api/detail", Behavior {
+        fail_first: usize::MAX,
+        fail_body: Some(json!({ "code": 429, "message": "请求过于频繁", "data": { "retry_after": 1e20 } })),
+        ..Default::default()
+    });
+    let client = FanqieClient::from_config(test_config(
+        &[&throttled.base_url, &healthy.base_url],
+        json!({ "max_retry_after": 60 }),
+    ))
+    .unwrap();
+
+    assert_eq!(client.api().get_book_detail("7001").await.unwrap().code, 200);
+
+    let remaining = client.api().node_health().throttle_remaining(&throttled.base_url).unwrap();
+    assert!(remaining > Duration::from_secs(50) && remaining <= Duration::from_secs(60), "{:?}", remaining);
+}
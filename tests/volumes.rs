@@ -0,0 +1,105 @@
+mod common;
+
+use serde_json::json;
+
+use common::{download_options, temp_dir, test_config, Behavior, MockBook, MockChapter, MockServer};
+use fqdl::client::FanqieClient;
+use fqdl::export::read_epub_chapters;
+use fqdl::update::UpdateOptions;
+
+fn volume_heading(volume: &str) -> String {
+    format!("\n{}\n{}\n", volume, "-".repeat(50))
+}
+
+#[tokio::test]
+async fn chapter_list_keeps_volume_grouping() {
+    let server = MockServer::start();
+    server.add_book(MockBook::sample("9201", 4));
+    server.set_behavior("/api/directory", Behavior {
+        fail_first: usize::MAX,
+        fail_body: Some(json!({ "code": 404, "message": "目录不存在" })),
+        ..Default::default()
+    });
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+
+    let chapters = client.downloader(download_options("9201", "", "txt")).get_chapters().await.unwrap();
+
+    assert_eq!(server.hits("/api/book"), 1);
+    let volumes: Vec<_> = chapters.iter().map(|c| c.volume_name.as_deref()).collect();
+    assert_eq!(volumes, [Some("第一卷"), Some("第一卷"), Some("第二卷"), Some("第二卷")]);
+}
+
+#[tokio::test]
+async fn txt_export_and_update_write_volume_headings() {
+    let server = MockServer::start();
+    let book = MockBook::sample("9202", 4);
+    server.add_book(book.clone());
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+
+    let dir = temp_dir("volumes-txt");
+    let path = client.downloader(download_options("9202", &dir, "txt")).download_book().await.unwrap();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    let first = text.find(&volume_heading("第一卷")).unwrap();
+    let second = text.find(&volume_heading("第二卷")).unwrap();
+    assert!(first < text.find(&book.chapters[0].title).unwrap());
+    assert!(text.find(&book.chapters[1].content).unwrap() < second);
+    assert!(second < text.find(&book.chapters[2].title).unwrap());
+
+    // 更新时只有进入新分卷才追加分卷标题
+    let mut longer = book.clone();
+    for (i, volume) in [(5, "第二卷"), (6, "第三卷")] {
+        longer.chapters.push(MockChapter {
+            chapter_id: format!("9202{:04}", i),
+            title: format!("第{}章 测试章节", i),
+            content: format!("第{}章正文。", i),
+            volume_name: volume.to_string(),
+        });
+    }
+    server.add_book(longer.clone());
+
+    let results = client.updater(UpdateOptions {
+        target: path.to_string_lossy().to_string(),
+        save_path: String::new(),
+        use_cache: false,
+        allow_partial: false,
+    }).run().await.unwrap();
+    assert_eq!(results[0].new_chapters, 2);
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(text.matches(&volume_heading("第二卷")).count(), 1);
+    let third = text.find(&volume_heading("第三卷")).unwrap();
+    assert!(text.find(&longer.chapters[4].content).unwrap() < third);
+    assert!(third < text.find(&longer.chapters[5].title).unwrap());
+}
+
+#[tokio::test]
+async fn epub_toc_nests_chapters_under_volumes() {
+    let server = MockServer::start();
+    let book = MockBook::sample("9203", 4);
+    server.add_book(book.clone());
+    let client = FanqieClient::from_config(test_config(&[&server.base_url], json!({}))).unwrap();
+
+    let dir = temp_dir("volumes-epub");
+    let path = client.downloader(download_options("9203", &dir, "epub")).download_book().await.unwrap();
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    assert!(archive.by_name("OEBPS/volume_0001.xhtml").is_ok());
+    assert!(archive.by_name("OEBPS/volume_0002.xhtml").is_ok());
+    let mut ncx = String::new();
+    std::io::Read::read_to_string(&mut archive.by_name("OEBPS/toc.ncx").unwrap(), &mut ncx).unwrap();
+
+    // 第一卷的 navPoint 在其章节之后才闭合
+    let first = ncx.find("第一卷").unwrap();
+    let second = ncx.find("第二卷").unwrap();
+    let first_volume = &ncx[first..second];
+    assert!(first_volume.contains(&book.chapters[0].title));
+    assert!(first_volume.contains(&book.chapters[1].title));
+    assert!(!first_volume[..first_volume.find(&book.chapters[0].title).unwrap()].contains("</navPoint>"));
+    assert!(ncx[second..].contains(&book.chapters[3].title));
+
+    // 分卷页不影响读回章节
+    let chapters = read_epub_chapters(&path).unwrap();
+    assert_eq!(chapters.len(), 4);
+    assert_eq!(chapters[2].title, book.chapters[2].title);
+}